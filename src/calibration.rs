@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use crate::intersection::Controller;
+use crate::network::Network;
+use crate::scenario;
+use crate::simulation::{Simulation, FRAMES_PER_SECOND, VEHICULE_LENGTH};
+use crate::vehicle::{Direction, Vehicle, VELOCITY};
+
+// Vehicles the approach is long enough to queue
+const QUEUE: i32 = 12;
+// Queue position from which vehicles cross at the saturation headway, as in the HCM
+const SATURATED_FROM: usize = 5;
+// Longest a queue may take to form or to discharge before giving up
+const MAX_FRAMES: i32 = 60 * FRAMES_PER_SECOND;
+
+/// Saturated discharge of one approach's lane, all its movements together.
+pub struct LaneGroup {
+    pub direction: Direction,
+    // Per cycle, the frames from the start of green to the first queued
+    // vehicle crossing the stop line, then between successive ones
+    pub headways: Vec<Vec<i32>>,
+    // Share of the cycle the approach is green for
+    pub green_share: f32,
+}
+
+impl LaneGroup {
+    /// Mean headway, in frames, of queue positions `SATURATED_FROM` and on.
+    pub fn saturation_headway(&self) -> Option<f32> {
+        let saturated: Vec<i32> = self
+            .headways
+            .iter()
+            .flat_map(|cycle| cycle.iter().skip(SATURATED_FROM - 1).copied())
+            .collect();
+        if saturated.is_empty() {
+            return None;
+        }
+        Some(saturated.iter().sum::<i32>() as f32 / saturated.len() as f32)
+    }
+
+    /// Vehicles per hour of green.
+    pub fn saturation_flow(&self) -> Option<f32> {
+        self.saturation_headway()
+            .map(|headway| 3600. * FRAMES_PER_SECOND as f32 / headway)
+    }
+
+    /// Frames the head of the queue takes over the saturation headway to get
+    /// going, summed over the positions before `SATURATED_FROM`.
+    pub fn start_up_lost_time(&self) -> Option<f32> {
+        let headway = self.saturation_headway()?;
+        let cycles = self.headways.len() as f32;
+        let lost: f32 = self
+            .headways
+            .iter()
+            .flat_map(|cycle| cycle.iter().take(SATURATED_FROM - 1))
+            .map(|&h| h as f32 - headway)
+            .sum();
+        Some(lost / cycles)
+    }
+
+    /// Vehicles per hour the lane serves under its share of green.
+    pub fn capacity(&self) -> Option<f32> {
+        self.saturation_flow().map(|flow| flow * self.green_share)
+    }
+}
+
+/// Queues each approach of an isolated intersection up behind a red, then
+/// gives it a continuous green and times the queue over the stop line,
+/// `cycles` times. Options are the scenario's; with a fixed-time `--plan`,
+/// capacities are for its green splits, otherwise for a continuous green.
+pub fn measure(options: &HashMap<String, String>, seed: u64, cycles: usize) -> Result<Vec<LaneGroup>, String> {
+    // The options set the spacing of the queue, and so the approach it needs
+    let approach = QUEUE * (VEHICULE_LENGTH + scenario::safe_distance(options)?);
+    let mut sim = Simulation::new(Network::isolated(approach), seed);
+    scenario::configure(&mut sim, options)?;
+    sim.demand = 0.;
+    let plan = match &sim.intersections[0].controller {
+        Controller::FixedTime(plan) => Some(plan.clone()),
+        _ => None,
+    };
+    sim.set_controller(|| Controller::External);
+
+    let mut groups = Vec::new();
+    for direction in [Direction::North, Direction::South, Direction::East, Direction::West] {
+        let green_share = plan.as_ref().map_or(1., |plan| {
+            let green: i32 = plan.phases.iter().filter(|(d, _)| *d == direction).map(|(_, g)| g).sum();
+            green as f32 / plan.cycle as f32
+        });
+        let mut headways = Vec::new();
+        for _ in 0..cycles {
+            headways.push(discharge(&mut sim, direction)?);
+        }
+        groups.push(LaneGroup {
+            direction,
+            headways,
+            green_share,
+        });
+    }
+    Ok(groups)
+}
+
+/// The safe distance that would turn a saturation headway of `headway`
+/// frames, measured with `safe_distance`, into a saturation flow of `flow`
+/// vehicles per hour: every extra pixel of spacing in a moving queue adds
+/// 1/`VELOCITY` of a frame to the headway. A first estimate, worth measuring
+/// again with.
+pub fn safe_distance_for(headway: f32, safe_distance: i32, flow: f32) -> i32 {
+    let target = 3600. * FRAMES_PER_SECOND as f32 / flow;
+    safe_distance + ((target - headway) * VELOCITY as f32).round() as i32
+}
+
+// Fills the approach heading `direction` behind a red, then turns it green and
+// returns the headways of the queue crossing the stop line
+fn discharge(sim: &mut Simulation, direction: Direction) -> Result<Vec<i32>, String> {
+    sim.intersections[0].set_green(None);
+    let waiting = |sim: &Simulation| -> Vec<u32> {
+        let mut queue: Vec<_> = sim.vehicles.iter().filter(|v| !crossed(v)).collect();
+        queue.sort_by_key(|v| v.distance_to_box());
+        queue.iter().map(|v| v.id).collect()
+    };
+
+    let mut frames = 0;
+    loop {
+        let spawned = sim.spawn_at(direction, 0);
+        sim.step();
+        let full = sim.vehicles.iter().filter(|v| !crossed(v)).all(|v| v.at_standstill());
+        if !spawned && full {
+            break;
+        }
+        frames += 1;
+        if frames > MAX_FRAMES {
+            return Err(format!("no queue formed heading {:?}", direction));
+        }
+    }
+
+    let queue = waiting(sim);
+    sim.intersections[0].set_green(Some(direction));
+    let mut headways = Vec::new();
+    let mut last = 0;
+    for frame in 1..=MAX_FRAMES {
+        sim.step();
+        let left = waiting(sim);
+        // Vehicles cross in queue order
+        while headways.len() < queue.len() && !left.contains(&queue[headways.len()]) {
+            headways.push(frame - last);
+            last = frame;
+        }
+        if headways.len() == queue.len() {
+            return Ok(headways);
+        }
+    }
+    Err(format!("queue heading {:?} did not discharge", direction))
+}
+
+// Whether the rear of `vehicle` is over the stop line, where headways are timed in the field
+fn crossed(vehicle: &Vehicle) -> bool {
+    vehicle.has_turned || vehicle.distance_to_box() <= -VEHICULE_LENGTH
+}
@@ -0,0 +1,131 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::VecDeque;
+
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::pressure::queue_length;
+use crate::simulation::{Simulation, FRAMES_PER_SECOND};
+use crate::traffic_light::LightState;
+use crate::vehicle::Direction;
+
+pub const PANEL_WIDTH: i32 = 340;
+const PADDING: i32 = 20;
+const LABEL_WIDTH: i32 = 20;
+// One sample every SAMPLE_FRAMES, one pixel column per sample
+const SAMPLE_FRAMES: i32 = 12;
+const HISTORY: usize = (PANEL_WIDTH - 2 * PADDING - LABEL_WIDTH) as usize;
+const QUEUE_CHART_HEIGHT: i32 = 160;
+const ROW_HEIGHT: i32 = 16;
+
+const HEADINGS: [(Direction, &str, Color); 4] = [
+    (Direction::North, "N", Color::RGB(80, 150, 255)),
+    (Direction::South, "S", Color::RGB(255, 160, 60)),
+    (Direction::East, "E", Color::RGB(120, 220, 120)),
+    (Direction::West, "W", Color::RGB(220, 120, 220)),
+];
+
+struct Sample {
+    queues: [i32; 4],
+    lights: [LightState; 4],
+}
+
+/// Side panel with rolling charts for one intersection: the queue on each
+/// approach, and which lights were green, over the last minute or so.
+#[derive(Default)]
+pub struct Charts {
+    pub node: usize,
+    samples: VecDeque<Sample>,
+}
+
+impl Charts {
+    /// Shows intersection `node` from now on, dropping the history of the previous one.
+    pub fn select(&mut self, node: usize) {
+        if node != self.node {
+            self.node = node;
+            self.samples.clear();
+        }
+    }
+
+    /// Samples the simulation; call once per frame, after stepping it.
+    pub fn update(&mut self, sim: &Simulation) {
+        if sim.frame_count % SAMPLE_FRAMES != 0 {
+            return;
+        }
+        let Some(intersection) = sim.intersections.get(self.node) else {
+            return;
+        };
+        self.samples.push_back(Sample {
+            queues: HEADINGS.map(|(d, _, _)| queue_length(&sim.vehicles, self.node, d, None)),
+            lights: HEADINGS.map(|(d, _, _)| intersection.light_for(d)),
+        });
+        if self.samples.len() > HISTORY {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Draws the panel with its left edge at `x`.
+    pub fn draw(&self, canvas: &mut Canvas<Window>, x: i32, height: i32) {
+        canvas.set_draw_color(Color::RGB(25, 25, 30));
+        let _ = canvas.fill_rect(Rect::new(x, 0, PANEL_WIDTH as u32, height as u32));
+
+        let left = x + PADDING;
+        let chart_left = left + LABEL_WIDTH;
+        let seconds = HISTORY as i32 * SAMPLE_FRAMES / FRAMES_PER_SECOND;
+        canvas.set_draw_color(Color::RGB(230, 230, 230));
+        font::draw_text(canvas, left, PADDING, 2, &format!("#{} LAST {}S", self.node, seconds));
+
+        // Queue length per approach, scaled to the longest queue on screen
+        let top = PADDING + 3 * GLYPH_HEIGHT * 2;
+        font::draw_text(canvas, left, top, 1, "QUEUE");
+        let chart_top = top + 2 * GLYPH_HEIGHT;
+        let max_queue = self
+            .samples
+            .iter()
+            .flat_map(|s| s.queues)
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        canvas.set_draw_color(Color::RGB(60, 60, 70));
+        let _ = canvas.draw_rect(Rect::new(chart_left, chart_top, HISTORY as u32, QUEUE_CHART_HEIGHT as u32));
+        canvas.set_draw_color(Color::RGB(170, 170, 170));
+        font::draw_text(canvas, left, chart_top, 1, &max_queue.to_string());
+        font::draw_text(canvas, left, chart_top + QUEUE_CHART_HEIGHT - GLYPH_HEIGHT, 1, "0");
+        let y_of = |queue: i32| chart_top + QUEUE_CHART_HEIGHT - 1 - queue * (QUEUE_CHART_HEIGHT - 2) / max_queue;
+        for (h, &(_, name, color)) in HEADINGS.iter().enumerate() {
+            canvas.set_draw_color(color);
+            let points: Vec<Point> = self
+                .samples
+                .iter()
+                .enumerate()
+                .map(|(i, s)| Point::new(chart_left + i as i32, y_of(s.queues[h])))
+                .collect();
+            let _ = canvas.draw_lines(points.as_slice());
+            font::draw_text(canvas, chart_left + h as i32 * 24, chart_top + QUEUE_CHART_HEIGHT + 6, 1, name);
+        }
+
+        // Phase timeline: one row per light, green where it was green
+        let top = chart_top + QUEUE_CHART_HEIGHT + 30;
+        canvas.set_draw_color(Color::RGB(230, 230, 230));
+        font::draw_text(canvas, left, top, 1, "PHASES");
+        let rows_top = top + 2 * GLYPH_HEIGHT;
+        for (h, &(_, name, _)) in HEADINGS.iter().enumerate() {
+            let y = rows_top + h as i32 * (ROW_HEIGHT + 4);
+            canvas.set_draw_color(Color::RGB(230, 230, 230));
+            font::draw_text(canvas, left, y + (ROW_HEIGHT - GLYPH_HEIGHT) / 2, 1, name);
+            canvas.set_draw_color(Color::RGB(60, 60, 70));
+            let _ = canvas.fill_rect(Rect::new(chart_left, y, HISTORY as u32, ROW_HEIGHT as u32));
+            for (i, s) in self.samples.iter().enumerate() {
+                let color = match s.lights[h] {
+                    LightState::Green => Color::RGB(20, 180, 20),
+                    LightState::Red => Color::RGB(150, 30, 30),
+                    LightState::FlashingRed => Color::RGB(90, 30, 30),
+                    LightState::FlashingYellow => Color::RGB(150, 120, 20),
+                };
+                canvas.set_draw_color(color);
+                let _ = canvas.fill_rect(Rect::new(chart_left + i as i32, y, 1, ROW_HEIGHT as u32));
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::flash::FlashCause;
+use crate::intersection::Intersection;
+use crate::safety::SafetyEvent;
+use crate::traffic_light::LightState;
+use crate::vehicle::{turned_direction, Direction, Turn};
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+const TURNS: [Turn; 3] = [Turn::Straight, Turn::Right, Turn::Left];
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::South,
+        Direction::South => Direction::North,
+        Direction::East => Direction::West,
+        Direction::West => Direction::East,
+    }
+}
+
+/// The compatibility matrix: whether two movements, each an approach heading
+/// and a turn, may have the green at the same time. Movements of one approach
+/// always may; movements leaving into the same lane never may. Right turns
+/// keep to their corner of the box, so they clash only through that merge.
+/// Otherwise only opposing through movements run side by side; lefts are not
+/// protected and cross everything coming from elsewhere.
+pub fn compatible(a: (Direction, Turn), b: (Direction, Turn)) -> bool {
+    if a.0 == b.0 {
+        return true;
+    }
+    if turned_direction(a.0, a.1) == turned_direction(b.0, b.1) {
+        return false;
+    }
+    if a.1 == Turn::Right || b.1 == Turn::Right {
+        return true;
+    }
+    a.0 == opposite(b.0) && a.1 == Turn::Straight && b.1 == Turn::Straight
+}
+
+/// Whether approaches `a` and `b` may be green together: a green light lets
+/// every movement of its approach go, so all pairs must be compatible.
+pub fn approaches_compatible(a: Direction, b: Direction) -> bool {
+    TURNS
+        .iter()
+        .all(|&ta| TURNS.iter().all(|&tb| compatible((a, ta), (b, tb))))
+}
+
+/// The pair of approaches of `intersection` showing conflicting greens, if any.
+pub fn conflicting_greens(intersection: &Intersection) -> Option<(Direction, Direction)> {
+    if intersection.is_unsignalized() {
+        return None;
+    }
+    let greens: Vec<Direction> = HEADINGS
+        .into_iter()
+        .filter(|&d| intersection.light_for(d) == LightState::Green)
+        .collect();
+    greens.iter().enumerate().find_map(|(i, &a)| {
+        greens[i + 1..]
+            .iter()
+            .find(|&&b| !approaches_compatible(a, b))
+            .map(|&b| (a, b))
+    })
+}
+
+/// Checks the lights of `intersection` like a hardware conflict monitor: on
+/// conflicting greens it latches the intersection into all-red flash, where
+/// every approach flashes red, and reports the fault.
+pub fn check(intersection: &mut Intersection, frame: i32) -> Option<SafetyEvent> {
+    let (a, b) = conflicting_greens(intersection)?;
+    intersection.start_flash(FlashCause::Fault);
+    Some(SafetyEvent::ConflictingGreen {
+        frame,
+        node: intersection.node,
+        a,
+        b,
+    })
+}
@@ -0,0 +1,52 @@
+use crate::intersection::Intersection;
+use crate::traffic_light::LightState;
+use crate::vehicle::{Direction, Vehicle, VELOCITY};
+
+// Distance before the box, in pixels, within which vehicles receive the broadcast
+pub const SPAT_RANGE: i32 = 400;
+// Slowest speed worth advising, in pixels per frame; below it the vehicle might as well stop
+const MIN_ADVISED_SPEED: f32 = 1.5;
+
+/// Signal Phase and Timing message: the light one approach of an
+/// intersection shows, and when and how it changes next if that is known.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spat {
+    pub node: usize,
+    pub direction: Direction,
+    pub state: LightState,
+    pub remaining: Option<i32>,
+    pub next: Option<LightState>,
+}
+
+/// The message `intersection` broadcasts to vehicles heading `direction`.
+pub fn broadcast(intersection: &Intersection, direction: Direction) -> Spat {
+    let light = intersection.light(direction);
+    Spat {
+        node: intersection.node,
+        direction,
+        state: intersection.light_for(direction),
+        remaining: light.remaining(),
+        next: light.next_state(),
+    }
+}
+
+/// Whether `vehicle`, still before the box, is close enough to hear its intersection.
+pub fn in_range(vehicle: &Vehicle) -> bool {
+    !vehicle.in_intersection && !vehicle.has_turned && (0..SPAT_RANGE).contains(&vehicle.distance_to_box())
+}
+
+/// Green light optimal speed advisory: the speed, in pixels per frame, at
+/// which `vehicle` reaches the box just as its red turns green, when it would
+/// otherwise have to stop and slowing down is enough to avoid it.
+pub fn advise(vehicle: &Vehicle, spat: &Spat) -> Option<f32> {
+    if !in_range(vehicle) || spat.state != LightState::Red || spat.next != Some(LightState::Green) {
+        return None;
+    }
+    let until_green = spat.remaining?;
+    let distance = vehicle.distance_to_box();
+    if distance >= until_green * VELOCITY {
+        return None;
+    }
+    let speed = distance as f32 / until_green as f32;
+    (speed >= MIN_ADVISED_SPEED).then_some(speed)
+}
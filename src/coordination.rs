@@ -0,0 +1,105 @@
+use crate::network::Network;
+use crate::vehicle::Direction;
+
+/// A fixed-time signal plan. Every intersection of a coordinated group shares
+/// the same cycle; `offset` shifts where in the common cycle this one starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedTimePlan {
+    pub cycle: i32,
+    pub offset: i32,
+    // Approach served and its green duration, in frames
+    pub phases: Vec<(Direction, i32)>,
+}
+
+impl FixedTimePlan {
+    /// Serves `main` first for `main_green` frames, then the three other
+    /// approaches for `minor_green` frames each.
+    pub fn new(main: Direction, main_green: i32, minor_green: i32) -> Self {
+        let mut phases = vec![(main, main_green)];
+        for direction in [Direction::South, Direction::West, Direction::North, Direction::East] {
+            if direction != main {
+                phases.push((direction, minor_green));
+            }
+        }
+        FixedTimePlan {
+            cycle: phases.iter().map(|&(_, green)| green).sum(),
+            offset: 0,
+            phases,
+        }
+    }
+
+    /// The approach holding the green at `frame`.
+    pub fn phase_at(&self, frame: i32) -> Direction {
+        self.locate(frame).0
+    }
+
+    /// Frames left, from `frame`, before the current green ends.
+    pub fn remaining_at(&self, frame: i32) -> i32 {
+        self.locate(frame).1
+    }
+
+    /// Frames from `frame` until `direction` next turns green, if the plan serves it.
+    pub fn until_green(&self, direction: Direction, frame: i32) -> Option<i32> {
+        let t = (frame - self.offset).rem_euclid(self.cycle);
+        let mut start = 0;
+        let mut until = None;
+        for &(served, green) in &self.phases {
+            if served == direction {
+                let wait = (start - t).rem_euclid(self.cycle);
+                until = Some(until.map_or(wait, |u: i32| u.min(wait)));
+            }
+            start += green;
+        }
+        until
+    }
+
+    fn locate(&self, frame: i32) -> (Direction, i32) {
+        let mut t = (frame - self.offset).rem_euclid(self.cycle);
+        for &(direction, green) in &self.phases {
+            if t < green {
+                return (direction, green - t);
+            }
+            t -= green;
+        }
+        (self.phases[0].0, self.phases[0].1)
+    }
+}
+
+/// Offsets (one per network node) so that a platoon released by the green at
+/// the first intersection reaches every downstream one as it turns green,
+/// travelling `direction` at `speed` pixels per frame.
+pub fn green_wave_offsets(network: &Network, direction: Direction, speed: f32, cycle: i32) -> Vec<i32> {
+    let along = |center: (i32, i32)| match direction {
+        Direction::North => -center.1,
+        Direction::South => center.1,
+        Direction::East => center.0,
+        Direction::West => -center.0,
+    };
+    let start = network.nodes.iter().map(|n| along(n.center)).min().unwrap_or(0);
+    network
+        .nodes
+        .iter()
+        .map(|n| {
+            let travel = (along(n.center) - start) as f32 / speed;
+            (travel.round() as i32).rem_euclid(cycle)
+        })
+        .collect()
+}
+
+/// One coordinated plan per node of `network`, progressing along `direction`.
+pub fn green_wave(
+    network: &Network,
+    direction: Direction,
+    speed: f32,
+    main_green: i32,
+    minor_green: i32,
+) -> Vec<FixedTimePlan> {
+    let base = FixedTimePlan::new(direction, main_green, minor_green);
+    green_wave_offsets(network, direction, speed, base.cycle)
+        .into_iter()
+        .map(|offset| FixedTimePlan {
+            offset,
+            ..base.clone()
+        })
+        .collect()
+}
@@ -0,0 +1,52 @@
+use crate::driver::STARTUP_REACTION_FRAMES;
+use crate::simulation::{FRAMES_PER_SECOND, VEHICULE_LENGTH};
+use crate::vehicle::{ACCELERATION, CREEP_SPEED, VELOCITY};
+
+const SPEED: f32 = VELOCITY as f32;
+
+/// Frames between successive vehicles of a standing queue crossing the stop
+/// line once it has turned green. Each driver reacts once the vehicle ahead
+/// has crept a full step clear, then covers the spacing of a stopped queue,
+/// `safe_distance` bumper to bumper.
+pub fn saturation_headway(safe_distance: i32) -> f32 {
+    STARTUP_REACTION_FRAMES as f32 + SPEED / CREEP_SPEED + (VEHICULE_LENGTH + safe_distance) as f32 / SPEED
+}
+
+/// Frames a vehicle pulling away loses against one already at VELOCITY.
+pub const ACCELERATION_LOST: f32 = (SPEED - CREEP_SPEED) * (SPEED - CREEP_SPEED) / (2. * ACCELERATION * SPEED);
+
+/// Frames at the start of each green not used at the saturation flow: the
+/// head driver's reaction and the time lost getting up to speed.
+pub const START_UP_LOST: f32 = STARTUP_REACTION_FRAMES as f32 + ACCELERATION_LOST;
+
+/// Vehicles per hour of green a saturated lane discharges.
+pub fn saturation_flow(safe_distance: i32) -> f32 {
+    3600. * FRAMES_PER_SECOND as f32 / saturation_headway(safe_distance)
+}
+
+/// Frames of green for `queue` stopped vehicles to cross the stop line: the
+/// start-up lost time, then one saturation headway for each vehicle behind
+/// the first.
+pub fn green_frames(queue: i32, safe_distance: i32) -> i32 {
+    if queue == 0 {
+        return 0;
+    }
+    let frames = START_UP_LOST + (queue - 1) as f32 * saturation_headway(safe_distance);
+    frames.ceil() as i32
+}
+
+/// Frames of green for `queue` stopped vehicles to discharge and for those
+/// still driving in, `arrivals` frames from the line, to cross it behind
+/// them, each at least a saturation headway after the one before.
+pub fn green_frames_with_arrivals(queue: i32, arrivals: &[i32], safe_distance: i32) -> i32 {
+    let headway = saturation_headway(safe_distance).ceil() as i32;
+    let mut arrivals = arrivals.to_vec();
+    arrivals.sort();
+    arrivals.into_iter().fold(green_frames(queue, safe_distance), |green, arrival| {
+        if green == 0 {
+            arrival
+        } else {
+            arrival.max(green + headway)
+        }
+    })
+}
@@ -0,0 +1,63 @@
+use rand::Rng;
+
+use crate::simulation::FRAMES_PER_SECOND;
+use crate::vehicle::VELOCITY;
+
+// Gap, in frames of travel, every driver accepts when they all behave alike
+pub const NOMINAL_GAP_FRAMES: i32 = 60;
+// Reaction time from standstill of the average driver, a quarter of a second
+pub const STARTUP_REACTION_FRAMES: i32 = FRAMES_PER_SECOND / 4;
+
+/// How one driver behaves. All drivers share the default unless driver
+/// variability is on, in which case each is sampled with `Driver::sample`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Driver {
+    // Frames between being free to move off from standstill and doing so
+    pub reaction_frames: i32,
+    // Cruising speed, in pixels per frame, at most VELOCITY
+    pub desired_speed: f32,
+    // Shortest gap in conflicting traffic, in frames of its travel, the driver will pull into
+    pub acceptable_gap: i32,
+    // Frames after its light turns red within which the driver, still moving, goes through anyway
+    pub yellow_running: i32,
+}
+
+impl Default for Driver {
+    fn default() -> Self {
+        Driver {
+            reaction_frames: STARTUP_REACTION_FRAMES,
+            desired_speed: VELOCITY as f32,
+            acceptable_gap: NOMINAL_GAP_FRAMES,
+            yellow_running: 0,
+        }
+    }
+}
+
+impl Driver {
+    /// A driver drawn from the population: reaction times of 0.2 to 0.8 s,
+    /// cruising at 75 to 100% of the speed limit, accepting gaps of 0.7 to
+    /// 1.5 s, and one in four pressing on for up to half a second of red.
+    /// Each parameter is triangular (the sum of two uniform draws), so
+    /// middling drivers are the most common.
+    pub fn sample<R: Rng>(rng: &mut R) -> Self {
+        let mut triangular = |low: f32, high: f32| {
+            let half = (high - low) / 2.;
+            low + rng.random_range(0.0..=half) + rng.random_range(0.0..=half)
+        };
+        let second = FRAMES_PER_SECOND as f32;
+        let reaction_frames = triangular(0.2 * second, 0.8 * second).round() as i32;
+        let desired_speed = triangular(0.75, 1.) * VELOCITY as f32;
+        let acceptable_gap = triangular(0.7 * second, 1.5 * second).round() as i32;
+        let yellow_running = if rng.random_bool(0.25) {
+            rng.random_range(1..=FRAMES_PER_SECOND / 2)
+        } else {
+            0
+        };
+        Driver {
+            reaction_frames,
+            desired_speed,
+            acceptable_gap,
+            yellow_running,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::simulation::FRAMES_PER_SECOND;
+
+// Scale of the world, chosen so VELOCITY is an urban 54 km/h
+const METRES_PER_PIXEL: f64 = 0.05;
+const MASS: f64 = 1500.;
+// Rolling resistance force and aerodynamic drag coefficient (half air density times drag area)
+const ROLLING_RESISTANCE: f64 = 0.01 * MASS * 9.81;
+const DRAG: f64 = 0.5 * 1.2 * 0.7;
+// Engine idling, in litres per second
+const IDLE_FUEL: f64 = 0.8 / 3600.;
+// Litres of petrol per joule delivered at the wheels, at 25% efficiency
+const FUEL_PER_JOULE: f64 = 1. / (0.25 * 34.2e6);
+const CO2_PER_LITRE: f64 = 2310.;
+
+/// Fuel burnt and CO2 emitted by one vehicle, from a simple power-based
+/// model: the engine idles throughout and burns extra in proportion to the
+/// energy it puts into the wheels, to speed up and to overcome rolling
+/// resistance and drag. Braking returns nothing, so every stop costs the
+/// kinetic energy of getting going again.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Emissions {
+    // Litres and grams
+    pub fuel: f64,
+    pub co2: f64,
+    // Speed over the previous frame, in metres per second
+    speed: f64,
+}
+
+impl Emissions {
+    /// Adds one frame in which the vehicle travelled `pixels`.
+    pub fn record(&mut self, pixels: f64) {
+        let dt = 1. / FRAMES_PER_SECOND as f64;
+        let distance = pixels * METRES_PER_PIXEL;
+        let speed = distance / dt;
+        let kinetic = 0.5 * MASS * (speed * speed - self.speed * self.speed);
+        let resistance = (ROLLING_RESISTANCE + DRAG * speed * speed) * distance;
+        let wheel_energy = (kinetic + resistance).max(0.);
+        let fuel = IDLE_FUEL * dt + wheel_energy * FUEL_PER_JOULE;
+        self.fuel += fuel;
+        self.co2 += fuel * CO2_PER_LITRE;
+        self.speed = speed;
+    }
+}
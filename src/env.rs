@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::intersection::Controller;
+use crate::pressure::queue_length;
+use crate::simulation::{Simulation, FRAMES_PER_SECOND};
+use crate::traffic_light::LightState;
+use crate::vehicle::Direction;
+
+// Frames simulated per step; the chosen phases are held for all of them
+pub const STEP_FRAMES: i32 = FRAMES_PER_SECOND;
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+/// Per intersection, the queue and the light of each approach, in
+/// North, South, East, West order of vehicle heading.
+pub struct Observation {
+    pub queues: Vec<[i32; 4]>,
+    pub lights: Vec<[LightState; 4]>,
+}
+
+impl Observation {
+    fn of(sim: &Simulation) -> Self {
+        Observation {
+            queues: sim
+                .intersections
+                .iter()
+                .map(|i| HEADINGS.map(|d| queue_length(&sim.vehicles, i.node, d, None)))
+                .collect(),
+            lights: sim
+                .intersections
+                .iter()
+                .map(|i| HEADINGS.map(|d| i.light_for(d)))
+                .collect(),
+        }
+    }
+
+    pub fn total_queued(&self) -> i32 {
+        self.queues.iter().flatten().sum()
+    }
+}
+
+impl fmt::Display for Observation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (queues, lights)) in self.queues.iter().zip(&self.lights).enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            let lights: String = lights
+                .iter()
+                .map(|l| match l {
+                    LightState::Green => 'G',
+                    LightState::Red => 'R',
+                    LightState::FlashingRed => 'r',
+                    LightState::FlashingYellow => 'y',
+                })
+                .collect();
+            write!(f, "{} {} {} {} {}", queues[0], queues[1], queues[2], queues[3], lights)?;
+        }
+        Ok(())
+    }
+}
+
+/// Gym-style environment: an agent picks the green approach of every
+/// intersection and is rewarded with minus the number of queued vehicles.
+pub struct Env<F: Fn(u64) -> Simulation> {
+    make: F,
+    // Episode length in frames
+    horizon: i32,
+    sim: Simulation,
+}
+
+impl<F: Fn(u64) -> Simulation> Env<F> {
+    /// `make` builds the scenario for a seed; its signals are taken over by the agent.
+    pub fn new(make: F, horizon: i32) -> Self {
+        let sim = make(0);
+        let mut env = Env { make, horizon, sim };
+        env.reset(0);
+        env
+    }
+
+    /// Starts a new episode with every light red.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.sim = (self.make)(seed);
+        self.sim.set_controller(|| Controller::External);
+        for intersection in &mut self.sim.intersections {
+            intersection.set_green(None);
+        }
+        Observation::of(&self.sim)
+    }
+
+    /// Gives the green to `actions[i]` at intersection `i` for `STEP_FRAMES` frames.
+    pub fn step(&mut self, actions: &[Direction]) -> Result<(Observation, f32, bool), String> {
+        if actions.len() != self.sim.intersections.len() {
+            return Err(format!(
+                "expected {} actions, got {}",
+                self.sim.intersections.len(),
+                actions.len()
+            ));
+        }
+        for (intersection, &direction) in self.sim.intersections.iter_mut().zip(actions) {
+            intersection.set_green(Some(direction));
+        }
+        for _ in 0..STEP_FRAMES {
+            self.sim.step();
+        }
+        let observation = Observation::of(&self.sim);
+        let reward = -observation.total_queued() as f32;
+        let done = self.sim.frame_count >= self.horizon;
+        Ok((observation, reward, done))
+    }
+}
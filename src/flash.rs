@@ -0,0 +1,57 @@
+use crate::intersection::Controller;
+use crate::stop_control::StopControl;
+use crate::traffic_light::LightState;
+use crate::vehicle::{Direction, Vehicle};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlashCause {
+    // Scheduled low-demand operation: the East-West road flashes yellow, the other red
+    Night,
+    // Latched by the conflict monitor: every approach flashes red until reset
+    Fault,
+}
+
+/// Flash mode: the lights blink instead of cycling, and vehicles sort out
+/// right of way among themselves as at stop signs.
+pub struct FlashControl {
+    pub cause: FlashCause,
+    // Controller to hand the lights back to when a night flash ends
+    pub previous: Box<Controller>,
+    stop: StopControl,
+}
+
+impl FlashControl {
+    pub fn new(cause: FlashCause, previous: Controller) -> Self {
+        let stop = match cause {
+            FlashCause::Night => StopControl::two_way(),
+            FlashCause::Fault => StopControl::all_way(),
+        };
+        FlashControl {
+            cause,
+            previous: Box::new(previous),
+            stop,
+        }
+    }
+
+    /// The light shown to vehicles heading `direction`.
+    pub fn light(&self, direction: Direction) -> LightState {
+        if self.stop.is_major(direction) {
+            LightState::FlashingYellow
+        } else {
+            LightState::FlashingRed
+        }
+    }
+
+    pub fn update(&mut self, node: usize, center: (i32, i32), vehicles: &[Vehicle], frame: i32) {
+        self.stop.update(node, center, vehicles, frame);
+    }
+
+    /// Flashing red only for the vehicle whose turn it is at the stop lines;
+    /// the others wait behind it as if the light were red.
+    pub fn signal_for(&self, vehicle: &Vehicle) -> LightState {
+        match self.light(vehicle.direction) {
+            LightState::FlashingRed if self.stop.signal_for(vehicle) != LightState::Green => LightState::Red,
+            light => light,
+        }
+    }
+}
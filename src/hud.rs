@@ -0,0 +1,158 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+use std::collections::VecDeque;
+
+use crate::flash::FlashCause;
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::intersection::Controller;
+use crate::pressure::queue_length;
+use crate::simulation::{Simulation, FRAMES_PER_SECOND};
+use crate::vehicle::Direction;
+
+const SCALE: i32 = 2;
+const LINE_HEIGHT: i32 = (GLYPH_HEIGHT + 3) * SCALE;
+const MARGIN: i32 = 10;
+// Intersections listed one per line; larger networks are summarised
+const MAX_LISTED: usize = 4;
+// How long a notice stays on screen
+const NOTICE_FRAMES: i32 = 3 * FRAMES_PER_SECOND;
+
+fn short_name(direction: Direction) -> char {
+    match direction {
+        Direction::North => 'N',
+        Direction::South => 'S',
+        Direction::East => 'E',
+        Direction::West => 'W',
+    }
+}
+
+fn seconds(frames: i32) -> f32 {
+    frames as f32 / FRAMES_PER_SECOND as f32
+}
+
+/// On-screen panel of live figures, drawn over the top-left corner.
+pub struct Hud {
+    pub visible: bool,
+    // Completed-vehicle count sampled every frame over the last minute
+    completed: VecDeque<(i32, u32)>,
+    // Last notice for the operator and the frame it was posted on
+    notice: Option<(String, i32)>,
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Hud {
+            visible: true,
+            completed: VecDeque::new(),
+            notice: None,
+        }
+    }
+}
+
+impl Hud {
+    /// Samples the simulation; call once per frame, after stepping it.
+    pub fn update(&mut self, sim: &Simulation) {
+        self.completed.push_back((sim.frame_count, sim.metrics.total.vehicles));
+        while let Some(&(frame, _)) = self.completed.front() {
+            if sim.frame_count - frame <= 60 * FRAMES_PER_SECOND {
+                break;
+            }
+            self.completed.pop_front();
+        }
+    }
+
+    /// Shows `message`, e.g. a refused operator action, for a few seconds.
+    pub fn notify(&mut self, message: String, frame: i32) {
+        self.notice = Some((message, frame));
+    }
+
+    /// Vehicles that left the network over the last minute.
+    fn throughput(&self) -> u32 {
+        match (self.completed.front(), self.completed.back()) {
+            (Some((_, first)), Some((_, last))) => last - first,
+            _ => 0,
+        }
+    }
+
+    fn lines(&self, sim: &Simulation) -> Vec<String> {
+        let mut lines = vec![format!("TIME {:.1}S", seconds(sim.frame_count))];
+
+        for intersection in sim.intersections.iter().take(MAX_LISTED) {
+            let phase = match intersection.green_direction() {
+                Some(direction) => format!("{} GREEN", short_name(direction)),
+                None => match &intersection.controller {
+                    Controller::Flash(flash) if flash.cause == FlashCause::Fault => "FAULT FLASH".to_string(),
+                    Controller::Flash(_) => "NIGHT FLASH".to_string(),
+                    _ if intersection.is_unsignalized() => "UNSIGNALIZED".to_string(),
+                    _ => "ALL RED".to_string(),
+                },
+            };
+            let remaining = intersection
+                .remaining_green()
+                .map(|frames| format!(" {:.1}S LEFT", seconds(frames)))
+                .unwrap_or_default();
+            let manual = match &intersection.controller {
+                Controller::Manual(manual) => {
+                    let waiting: String = manual.waiting().iter().map(|&d| short_name(d)).collect();
+                    if waiting.is_empty() {
+                        " MANUAL".to_string()
+                    } else {
+                        format!(" MANUAL, {} CLEARING", waiting)
+                    }
+                }
+                _ => String::new(),
+            };
+            lines.push(format!("#{} {}{}{}", intersection.node, phase, remaining, manual));
+        }
+        if sim.intersections.len() > MAX_LISTED {
+            lines.push(format!("({} MORE)", sim.intersections.len() - MAX_LISTED));
+        }
+
+        let queues: Vec<String> = [Direction::North, Direction::South, Direction::East, Direction::West]
+            .into_iter()
+            .map(|d| {
+                let queued: i32 = sim
+                    .intersections
+                    .iter()
+                    .map(|i| queue_length(&sim.vehicles, i.node, d, None))
+                    .sum();
+                format!("{}{}", short_name(d), queued)
+            })
+            .collect();
+        lines.push(format!("QUEUE {}", queues.join(" ")));
+        lines.push(format!("THROUGHPUT {}/MIN", self.throughput()));
+        lines.push(format!("AVG DELAY {:.1}S", sim.metrics.total.average_delay()));
+        if let Some((message, frame)) = &self.notice {
+            if sim.frame_count - frame < NOTICE_FRAMES {
+                lines.push(message.clone());
+            }
+        }
+        lines
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, sim: &Simulation) {
+        if !self.visible {
+            return;
+        }
+        let lines = self.lines(sim);
+        let width = lines.iter().map(|l| font::text_width(l, SCALE)).max().unwrap_or(0);
+        let height = lines.len() as i32 * LINE_HEIGHT;
+
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 170));
+        let _ = canvas.fill_rect(Rect::new(
+            MARGIN,
+            MARGIN,
+            (width + 2 * MARGIN) as u32,
+            (height + MARGIN) as u32,
+        ));
+        canvas.set_blend_mode(BlendMode::None);
+
+        canvas.set_draw_color(Color::RGB(230, 230, 230));
+        for (i, line) in lines.iter().enumerate() {
+            font::draw_text(canvas, 2 * MARGIN, 3 * MARGIN / 2 + i as i32 * LINE_HEIGHT, SCALE, line);
+        }
+    }
+}
@@ -3,27 +3,349 @@ use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 
-pub fn draw(canvas: &mut Canvas<Window>) {
+use crate::coordination::FixedTimePlan;
+use crate::discharge;
+use crate::flash::{FlashCause, FlashControl};
+use crate::manual::ManualControl;
+use crate::pressure::{self, QueueControl};
+use crate::reservation::ReservationManager;
+use crate::roundabout;
+use crate::stop_control::StopControl;
+use crate::traffic_light::{LightState, TrafficLight};
+use crate::vehicle::{Direction, Vehicle};
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+pub enum Controller {
+    // Serves approaches in rotation, sizing each green from its queue
+    Adaptive,
+    FixedTime(FixedTimePlan),
+    // Serves whichever approach its queues favour, see `QueuePolicy`
+    Queue(QueueControl),
+    // Lights are set from outside, e.g. by a learning agent, and held
+    External,
+    // Lights are set by an operator, with clearance intervals enforced
+    Manual(ManualControl),
+    // No signals: vehicles give way to the circulating traffic of a roundabout
+    Roundabout,
+    StopSign(StopControl),
+    // No signals: vehicles reserve their path through the box, see `ReservationManager`
+    Reservation(ReservationManager),
+    // Lights blink on a night schedule or after a fault, see `FlashControl`
+    Flash(FlashControl),
+}
+
+/// A signalized intersection: its four lights and the controller cycling them.
+pub struct Intersection {
+    pub node: usize,
+    pub center: (i32, i32),
+    pub light_n: TrafficLight,
+    pub light_s: TrafficLight,
+    pub light_e: TrafficLight,
+    pub light_w: TrafficLight,
+    pub controller: Controller,
+    n: i32,
+    // Length of the adaptive controller's current green
+    green_frames: i32,
+    current_light: u8,
+    // Whether flashing lights are lit on this frame
+    flash_on: bool,
+}
+
+impl Intersection {
+    pub fn new(node: usize, center: (i32, i32)) -> Self {
+        let (cx, cy) = center;
+        Intersection {
+            node,
+            center,
+            light_s: TrafficLight::new(cx + 60, cy + 60, 20, 20, LightState::Red),
+            light_w: TrafficLight::new(cx - 80, cy + 60, 20, 20, LightState::Red),
+            light_n: TrafficLight::new(cx - 80, cy - 80, 20, 20, LightState::Green),
+            light_e: TrafficLight::new(cx + 60, cy - 80, 20, 20, LightState::Red),
+            controller: Controller::Adaptive,
+            n: 0,
+            green_frames: 0,
+            current_light: 1,
+            flash_on: false,
+        }
+    }
+
+    /// Advances the controller by one frame.
+    pub fn update(&mut self, vehicles: &[Vehicle], frame: i32, safe_distance: i32) {
+        match &mut self.controller {
+            Controller::Adaptive => self.update_adaptive(vehicles, safe_distance),
+            Controller::FixedTime(plan) => {
+                let direction = plan.phase_at(frame);
+                self.set_green(Some(direction));
+            }
+            Controller::Queue(queue) => {
+                let direction = queue.update(self.node, vehicles, frame);
+                self.set_green(direction);
+            }
+            Controller::Manual(manual) => {
+                manual.update(frame);
+                let greens = manual.greens().to_vec();
+                self.set_lights(|d| greens.contains(&d));
+            }
+            Controller::External | Controller::Roundabout => {}
+            Controller::StopSign(stop) => stop.update(self.node, self.center, vehicles, frame),
+            Controller::Reservation(manager) => manager.update(self.node, self.center, vehicles, frame, safe_distance),
+            Controller::Flash(flash) => {
+                flash.update(self.node, self.center, vehicles, frame);
+                self.flash_on = frame / 30 % 2 == 0;
+            }
+        }
+        self.update_schedules(frame);
+    }
+
+    // Tells each light how long it keeps its state, where the controller has fixed that
+    fn update_schedules(&mut self, frame: i32) {
+        for direction in HEADINGS {
+            let state = self.light_for(direction);
+            let schedule = match &self.controller {
+                Controller::Adaptive if state == LightState::Green => {
+                    Some(((self.green_frames - self.n + 1).max(1), LightState::Red))
+                }
+                Controller::FixedTime(plan) if state == LightState::Green => {
+                    Some((plan.remaining_at(frame), LightState::Red))
+                }
+                Controller::FixedTime(plan) => plan
+                    .until_green(direction, frame)
+                    .map(|frames| (frames, LightState::Green)),
+                _ => None,
+            };
+            self.light_mut(direction).set_schedule(schedule);
+        }
+    }
+
+    // Moves to the next approach once the current green has run out
+    fn update_adaptive(&mut self, vehicles: &[Vehicle], safe_distance: i32) {
+        self.n += 1;
+        if self.n > self.green_frames {
+            self.n = 0;
+            let direction = match self.current_light {
+                0 => Direction::South,
+                1 => Direction::West,
+                2 => Direction::North,
+                3 => Direction::East,
+                _ => unreachable!(),
+            };
+
+            // Just long enough for the queue standing now to discharge and
+            // the vehicles still driving in to follow it through
+            let queue = pressure::queue_length(vehicles, self.node, direction, None);
+            let arrivals = pressure::arrivals(vehicles, self.node, direction);
+            self.green_frames = discharge::green_frames_with_arrivals(queue, &arrivals, safe_distance);
+            if self.green_frames != 0 {
+                self.set_green(Some(direction));
+            } else {
+                self.set_green(None);
+            }
+
+            self.current_light = (self.current_light + 1) % 4;
+        }
+    }
+
+    /// Gives the green to the approach of vehicles heading `direction`, red to
+    /// every other one.
+    pub fn set_green(&mut self, direction: Option<Direction>) {
+        self.set_lights(|d| Some(d) == direction);
+    }
+
+    // Sets each light from whether the vehicles facing it get the green
+    fn set_lights(&mut self, is_green: impl Fn(Direction) -> bool) {
+        self.light_s.update(is_green(Direction::North));
+        self.light_n.update(is_green(Direction::South));
+        self.light_w.update(is_green(Direction::East));
+        self.light_e.update(is_green(Direction::West));
+    }
+
+    /// Switches the lights to flash mode, keeping the controller to return to.
+    /// A fault overrides a night flash; nothing else changes a running flash.
+    pub fn start_flash(&mut self, cause: FlashCause) {
+        let previous = match std::mem::replace(&mut self.controller, Controller::External) {
+            Controller::Flash(flash) if cause == FlashCause::Fault => *flash.previous,
+            controller @ (Controller::Roundabout
+            | Controller::StopSign(_)
+            | Controller::Reservation(_)
+            | Controller::Flash(_)) => {
+                self.controller = controller;
+                return;
+            }
+            controller => controller,
+        };
+        let flash = FlashControl::new(cause, previous);
+        self.light_s.state = flash.light(Direction::North);
+        self.light_n.state = flash.light(Direction::South);
+        self.light_w.state = flash.light(Direction::East);
+        self.light_e.state = flash.light(Direction::West);
+        self.controller = Controller::Flash(flash);
+    }
+
+    /// Ends a night flash, handing the lights back all red to the controller
+    /// they were taken from. Faults stay latched.
+    pub fn end_flash(&mut self) {
+        if !matches!(&self.controller, Controller::Flash(flash) if flash.cause == FlashCause::Night) {
+            return;
+        }
+        if let Controller::Flash(flash) = std::mem::replace(&mut self.controller, Controller::External) {
+            self.controller = *flash.previous;
+        }
+        self.set_green(None);
+    }
+
+    /// Hands the lights to an operator, or back to the controller they were
+    /// taken from. Unsignalized intersections have no lights to hand over.
+    pub fn toggle_manual(&mut self, frame: i32) {
+        if self.is_unsignalized() {
+            return;
+        }
+        let greens = HEADINGS.into_iter().filter(|&d| self.light_for(d) == LightState::Green).collect();
+        self.controller = match std::mem::replace(&mut self.controller, Controller::External) {
+            Controller::Manual(manual) => *manual.previous,
+            other => Controller::Manual(ManualControl::new(other, greens, frame)),
+        };
+    }
+
+    fn light_mut(&mut self, direction: Direction) -> &mut TrafficLight {
+        match direction {
+            Direction::North => &mut self.light_s,
+            Direction::South => &mut self.light_n,
+            Direction::East => &mut self.light_w,
+            Direction::West => &mut self.light_e,
+        }
+    }
+
+    /// The light facing vehicles heading `direction`.
+    pub fn light_for(&self, direction: Direction) -> LightState {
+        if let Controller::Flash(flash) = &self.controller {
+            return flash.light(direction);
+        }
+        if self.is_unsignalized() {
+            return LightState::Green;
+        }
+        self.light(direction).state
+    }
+
+    /// The heading of the vehicles facing the light drawn at `(x, y)`, if any.
+    pub fn light_at(&self, x: i32, y: i32) -> Option<Direction> {
+        if self.is_unsignalized() {
+            return None;
+        }
+        [
+            (&self.light_s, Direction::North),
+            (&self.light_n, Direction::South),
+            (&self.light_w, Direction::East),
+            (&self.light_e, Direction::West),
+        ]
+        .into_iter()
+        .find(|(light, _)| light.contains(x, y))
+        .map(|(_, direction)| direction)
+    }
+
+    /// The heading of the vehicles holding the green, if any.
+    pub fn green_direction(&self) -> Option<Direction> {
+        if self.is_unsignalized() {
+            return None;
+        }
+        HEADINGS.into_iter().find(|&d| self.light_for(d) == LightState::Green)
+    }
+
+    /// The light facing vehicles heading `direction`, for its timing.
+    pub fn light(&self, direction: Direction) -> &TrafficLight {
+        match direction {
+            Direction::North => &self.light_s,
+            Direction::South => &self.light_n,
+            Direction::East => &self.light_w,
+            Direction::West => &self.light_e,
+        }
+    }
+
+    /// Frames left in the current green, when the controller has fixed its
+    /// length in advance.
+    pub fn remaining_green(&self) -> Option<i32> {
+        self.light(self.green_direction()?).remaining()
+    }
+
+    /// What `vehicle` sees at its stop line: green when it may enter the box.
+    pub fn signal_for(&self, vehicle: &Vehicle) -> LightState {
+        match &self.controller {
+            Controller::StopSign(stop) => stop.signal_for(vehicle),
+            Controller::Reservation(manager) => manager.signal_for(vehicle),
+            Controller::Flash(flash) => flash.signal_for(vehicle),
+            _ => self.light_for(vehicle.direction),
+        }
+    }
+
+    pub fn is_unsignalized(&self) -> bool {
+        matches!(
+            self.controller,
+            Controller::Roundabout | Controller::StopSign(_) | Controller::Reservation(_) | Controller::Flash(_)
+        )
+    }
+
+    /// Countdowns beside the lights whose timing is known.
+    pub fn draw_countdowns(&self, canvas: &mut Canvas<Window>) {
+        if self.is_unsignalized() {
+            return;
+        }
+        for light in [&self.light_n, &self.light_s, &self.light_e, &self.light_w] {
+            light.draw_countdown(canvas);
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+        match &self.controller {
+            Controller::Roundabout => {
+                roundabout::draw(canvas, self.center);
+                return;
+            }
+            Controller::StopSign(stop) => {
+                draw(canvas, self.center);
+                stop.draw(canvas, self.center);
+                return;
+            }
+            Controller::Reservation(manager) => {
+                draw(canvas, self.center);
+                manager.draw(canvas, self.center);
+                return;
+            }
+            Controller::Flash(_) if !self.flash_on => {
+                draw(canvas, self.center);
+                return;
+            }
+            _ => {}
+        }
+        draw(canvas, self.center);
+        self.light_n.draw(canvas);
+        self.light_s.draw(canvas);
+        self.light_e.draw(canvas);
+        self.light_w.draw(canvas);
+    }
+}
+
+pub fn draw(canvas: &mut Canvas<Window>, center: (i32, i32)) {
+    let (x, y) = (center.0 - 50, center.1 - 50);
     let crossing_color = Color::RGB(45, 45, 50);
     canvas.set_draw_color(crossing_color);
     // Intersection square
-    let square = Rect::new(350, 350, 100, 100);
+    let square = Rect::new(x, y, 100, 100);
     let _ = canvas.fill_rect(square);
 
     canvas.set_draw_color(Color::RGB(220, 220, 220));
 
     // Horizontal crosswalk stripes (top and bottom of intersection)
     for i in 0..5 {
-        let stripe_top = Rect::new(355 + i * 18, 345, 12, 10);
-        let stripe_bottom = Rect::new(355 + i * 18, 445, 12, 10);
+        let stripe_top = Rect::new(x + 5 + i * 18, y - 5, 12, 10);
+        let stripe_bottom = Rect::new(x + 5 + i * 18, y + 95, 12, 10);
         let _ = canvas.fill_rect(stripe_top);
         let _ = canvas.fill_rect(stripe_bottom);
     }
 
     // Vertical crosswalk stripes (left and right of intersection)
     for i in 0..5 {
-        let stripe_left = Rect::new(345, 355 + i * 18, 10, 12);
-        let stripe_right = Rect::new(445, 355 + i * 18, 10, 12);
+        let stripe_left = Rect::new(x - 5, y + 5 + i * 18, 10, 12);
+        let stripe_right = Rect::new(x + 95, y + 5 + i * 18, 10, 12);
         let _ = canvas.fill_rect(stripe_left);
         let _ = canvas.fill_rect(stripe_right);
     }
@@ -0,0 +1,34 @@
+// src/lib.rs
+pub mod calibration;
+pub mod charts;
+pub mod conflict_monitor;
+pub mod connected;
+pub mod coordination;
+pub mod discharge;
+pub mod driver;
+pub mod emissions;
+pub mod env;
+pub mod flash;
+pub mod font;
+pub mod hud;
+pub mod intersection;
+pub mod manual;
+pub mod metrics;
+pub mod mouse;
+pub mod network;
+pub mod pressure;
+#[cfg(feature = "python")]
+mod python;
+pub mod reservation;
+pub mod right_turn_on_red;
+pub mod road;
+pub mod roundabout;
+pub mod routing;
+pub mod safety;
+pub mod scenario;
+pub mod simulation;
+pub mod stop_control;
+pub mod sweep;
+pub mod traffic_light;
+pub mod vehicle;
+pub mod webster;
@@ -1,31 +1,70 @@
 // src/main.rs
-mod intersection;
-mod road;
-mod traffic_light;
-mod vehicle;
-
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
 use sdl2::pixels::Color;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::time::Duration;
 use rand::Rng;
 
-use traffic_light::{LightState, TrafficLight};
-use vehicle::{Direction, Vehicle};
+use road_intersection::calibration;
+use road_intersection::charts::{Charts, PANEL_WIDTH};
+use road_intersection::env::Env;
+use road_intersection::hud::Hud;
+use road_intersection::intersection::Controller;
+use road_intersection::mouse::Mouse;
+use road_intersection::safety::SafetyEvent;
+use road_intersection::scenario;
+use road_intersection::simulation::FRAMES_PER_SECOND;
+use road_intersection::sweep;
+use road_intersection::vehicle::Direction;
 
-use road::Road;
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (positional, options) = split_options(&args)?;
+    if positional.first().map(String::as_str) == Some("env") {
+        return run_env(&positional[1..], &options);
+    }
+    if positional.first().map(String::as_str) == Some("sweep") {
+        return run_sweep(&positional[1..], options);
+    }
+    if positional.first().map(String::as_str) == Some("calibrate") {
+        return run_calibrate(options);
+    }
 
-const SAFE_DISTANCE: i32 = 50;
-const SINGLE_ROAD_PART: i32 = 350;
-const VEHICULE_LENGTH: i32 = 40;
+    let seed = match options.get("seed") {
+        Some(seed) => seed.parse().map_err(|_| format!("invalid seed: {}", seed))?,
+        None => rand::random(),
+    };
+    let mut sim = scenario::build(&positional, &options, seed)?;
+    let mut event_log = match options.get("log-events") {
+        Some(path) => Some(BufWriter::new(
+            File::create(path).map_err(|e| format!("cannot write {}: {}", path, e))?,
+        )),
+        None => None,
+    };
+
+    // Headless run: simulate a fixed number of frames and report the metrics
+    if let Some(frames) = options.get("frames") {
+        let frames: i32 = frames.parse().map_err(|_| format!("invalid frames: {}", frames))?;
+        for _ in 0..frames {
+            log_events(&mut event_log, sim.step())?;
+        }
+        println!("{}", sim.metrics);
+        return Ok(());
+    }
 
-fn main() -> Result<(), String> {
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
 
     let window = video_subsystem
-        .window("Road Intersection", 800, 800)
+        .window(
+            "Road Intersection",
+            (sim.network.width + PANEL_WIDTH) as u32,
+            sim.network.height as u32,
+        )
         .position_centered()
         .build()
         .map_err(|e| e.to_string())?;
@@ -40,27 +79,13 @@ fn main() -> Result<(), String> {
     canvas.clear();
     canvas.present();
 
-    let mut light_s = TrafficLight::new(460, 460, 20, 20, LightState::Red);
-    let mut light_w = TrafficLight::new(320, 460, 20, 20, LightState::Red);
-    let mut light_n = TrafficLight::new(320, 320, 20, 20, LightState::Green);
-    let mut light_e = TrafficLight::new(460, 320, 20, 20, LightState::Red);
-    let mut vehicles = Vec::new();
-
-    let mut last_spawn = HashMap::new();
-    last_spawn.insert(Direction::North, 0);
-    last_spawn.insert(Direction::South, 0);
-    last_spawn.insert(Direction::East, 0);
-    last_spawn.insert(Direction::West, 0);
-
     let mut event_pump = sdl_context.event_pump()?;
-    let mut n = 0;
-    let mut green_timer = 0.;
-    let mut frame_count = 0;
-    let mut current_light: u8 = 1;
+    let mut hud = Hud::default();
+    let mut charts = Charts::default();
+    let mut mouse = Mouse::default();
+    let mut countdowns = false;
 
     'running: loop {
-        frame_count += 1;
-
         // Input handling
         for event in event_pump.poll_iter() {
             match event {
@@ -74,6 +99,7 @@ fn main() -> Result<(), String> {
                         Keycode::Down => Some(Direction::South),
                         Keycode::Left => Some(Direction::West),
                         Keycode::Right => Some(Direction::East),
+                        #[allow(clippy::let_and_return)]
                         Keycode::R => {
                             let mut rng = rand::rng();
                             let val = rng.random_range(0..4);
@@ -86,211 +112,263 @@ fn main() -> Result<(), String> {
                             };
                             res
                         },
+                        Keycode::H => {
+                            hud.visible = !hud.visible;
+                            None
+                        }
+                        Keycode::C => {
+                            countdowns = !countdowns;
+                            None
+                        }
+                        // Chart, and operate by hand, the next intersection
+                        Keycode::Tab => {
+                            charts.select((charts.node + 1) % sim.intersections.len());
+                            None
+                        }
+                        Keycode::M => {
+                            sim.intersections[charts.node].toggle_manual(sim.frame_count);
+                            None
+                        }
+                        Keycode::Num1 | Keycode::Num2 | Keycode::Num3 | Keycode::Num4 => {
+                            let direction = match keycode {
+                                Keycode::Num1 => Direction::North,
+                                Keycode::Num2 => Direction::South,
+                                Keycode::Num3 => Direction::East,
+                                _ => Direction::West,
+                            };
+                            if let Controller::Manual(manual) = &mut sim.intersections[charts.node].controller {
+                                if let Err(e) = manual.toggle(direction, sim.frame_count) {
+                                    hud.notify(e, sim.frame_count);
+                                }
+                            }
+                            None
+                        }
                         Keycode::Escape => break 'running,
                         _ => None,
                     };
 
                     if let Some(dir) = direction {
-                        if is_safe_to_spawn(&vehicles, dir, &last_spawn, frame_count) {
-                            vehicles.push(Vehicle::new(dir));
-                            last_spawn.insert(dir, frame_count);
-                        }
+                        sim.spawn(dir);
                     }
                 }
+                Event::MouseButtonDown {
+                    mouse_btn: MouseButton::Left,
+                    x,
+                    y,
+                    ..
+                } => mouse.press(&mut sim, x, y),
+                Event::MouseMotion { x, y, .. } => mouse.drag(&sim.network, x, y),
+                Event::MouseButtonUp {
+                    mouse_btn: MouseButton::Left,
+                    ..
+                } => mouse.release(),
                 _ => {}
             }
         }
+        mouse.update(&mut sim);
 
-        n += 1;
-        if n as f32 > green_timer * 150. {
-            n = 0;
-             let direction = match current_light {
-                0 => Direction::South,
-                1 => Direction::West,
-                2 => Direction::North,
-                3 => Direction::East,
-                _ => unreachable!(),
-            };
-            // println!("{} {} {} {} {:?}", green_timer, green_timer * 150., n, current_light, direction);
-
-            green_timer = get_timer(&vehicles, direction);
-            // Turn all lights red
-            light_n.update(false);
-            light_s.update(false);
-            light_e.update(false);
-            light_w.update(false);
-
-            if green_timer != 0. {
-                match current_light {
-                    0 => light_n.update(true),
-                    1 => light_e.update(true),
-                    2 => light_s.update(true),
-                    3 => light_w.update(true),
-                    _ => unreachable!(),
-                };
-            }
-
-            current_light = (current_light + 1) % 4;
-        }
-
-        // Compute tentative positions (with traffic light checks)
-        let mut tentatives: Vec<Vehicle> = vehicles
-            .iter()
-            .map(|v| {
-                let mut tentative_v = v.clone();
-                let light_state = match tentative_v.direction {
-                    Direction::North => light_s.state,
-                    Direction::South => light_n.state,
-                    Direction::East => light_w.state,
-                    Direction::West => light_e.state,
-                };
-
-                tentative_v.update(light_state);
-                tentative_v
-            })
-            .collect();
+        log_events(&mut event_log, sim.step())?;
+        hud.update(&sim);
+        charts.update(&sim);
 
-        let mut safe_to_move = vec![true; tentatives.len()];
-        for i in 0..tentatives.len() {
-            let (before, rest) = tentatives.split_at_mut(i);
-            let (current, after) = rest.split_first_mut().unwrap();
-            let dir = current.direction;
-
-            let closest_ahead = before
-                .iter()
-                .chain(after.iter())
-                .filter(|other| other.direction == dir)
-                .filter_map(|other| {
-                    let distance = match dir {
-                        Direction::North => {
-                            current.rect.y() - (other.rect.y() + other.rect.height() as i32)
-                        }
-                        Direction::South => {
-                            other.rect.y() - (current.rect.y() + current.rect.height() as i32)
-                        }
-                        Direction::East => {
-                            other.rect.x() - (current.rect.x() + current.rect.width() as i32)
-                        }
-                        Direction::West => {
-                            current.rect.x() - (other.rect.x() + other.rect.width() as i32)
-                        }
-                    };
-                    if distance >= 0 {
-                        Some(distance)
-                    } else {
-                        None
-                    }
-                })
-                .min();
-
-            if let Some(distance) = closest_ahead {
-                if distance < SAFE_DISTANCE {
-                    safe_to_move[i] = false;
-                }
+        canvas.set_draw_color(Color::RGB(20, 40, 20));
+        canvas.clear();
+        sim.draw(&mut canvas);
+        if countdowns {
+            for intersection in &sim.intersections {
+                intersection.draw_countdowns(&mut canvas);
             }
         }
+        mouse.draw(&mut canvas, &sim);
+        charts.draw(&mut canvas, sim.network.width, sim.network.height);
+        hud.draw(&mut canvas, &sim);
+        canvas.present();
 
+        std::thread::sleep(Duration::from_millis(16));
+    }
+    println!("{}", sim.metrics);
+    Ok(())
+}
 
-        let mut collisions = vec![false; tentatives.len()];
-        for i in 0..tentatives.len() {
-            for j in (i + 1)..tentatives.len() {
-                let a = &tentatives[i];
-                let b = &tentatives[j];
-
-                if (a.in_intersection || b.in_intersection) && !a.has_turned && !b.has_turned {
-                    continue;
-                }
-
-                if a.rect.has_intersection(b.rect) {
-                    collisions[i] = true;
-                    collisions[j] = true;
+// Reinforcement-learning environment over stdin/stdout, one command per line:
+// `reset <seed>` starts an episode, `step <heading>...` (one of n, s, e, w per
+// intersection) plays one step. Each answer is the observation, plus the
+// reward and whether the episode is over after a step.
+fn run_env(layout: &[String], options: &HashMap<String, String>) -> Result<(), String> {
+    let horizon = match options.get("frames") {
+        Some(frames) => frames.parse().map_err(|_| format!("invalid frames: {}", frames))?,
+        None => 3600,
+    };
+    // Surface option errors now rather than on the first reset
+    scenario::build(layout, options, 0)?;
+    let mut env = Env::new(
+        |seed| scenario::build(layout, options, seed).expect("options were validated"),
+        horizon,
+    );
+
+    for line in std::io::stdin().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let mut words = line.split_whitespace();
+        let answer = match words.next() {
+            Some("reset") => match words.next().unwrap_or("0").parse() {
+                Ok(seed) => env.reset(seed).to_string(),
+                Err(_) => format!("error: invalid seed: {}", line),
+            },
+            Some("step") => {
+                let actions: Result<Vec<Direction>, String> = words
+                    .map(|word| match word {
+                        "n" => Ok(Direction::North),
+                        "s" => Ok(Direction::South),
+                        "e" => Ok(Direction::East),
+                        "w" => Ok(Direction::West),
+                        other => Err(format!("unknown heading: {}", other)),
+                    })
+                    .collect();
+                match actions.and_then(|actions| env.step(&actions)) {
+                    Ok((observation, reward, done)) => format!("{} ; {} {}", observation, reward, done),
+                    Err(e) => format!("error: {}", e),
                 }
             }
-        }
+            None => continue,
+            Some(other) => format!("error: unknown command: {}", other),
+        };
+        println!("{}", answer);
+    }
+    Ok(())
+}
 
-        // Update original vehicles only if safe
-        for (i, vehicle) in vehicles.iter_mut().enumerate() {
-            if safe_to_move[i] && !collisions[i] {
-                *vehicle = tentatives[i].clone();
-            }
-        }
-        vehicles.retain(|v| is_on_screen(v));
+// Runs every combination of the scenario options headlessly, in parallel, and
+// writes one CSV row per run. Options take lists and ranges, see `sweep::expand`:
+// `sweep grid --demand 5..20:5 --plan adaptive;max-pressure --seed 1..10`.
+// `--frames` (default 5 minutes), `--threads` and `--out` apply to the sweep.
+fn run_sweep(layout: &[String], mut options: HashMap<String, String>) -> Result<(), String> {
+    let frames = match options.remove("frames") {
+        Some(frames) => frames.parse().map_err(|_| format!("invalid frames: {}", frames))?,
+        None => 5 * 60 * FRAMES_PER_SECOND,
+    };
+    let threads = match options.remove("threads") {
+        Some(threads) => threads.parse().map_err(|_| format!("invalid threads: {}", threads))?,
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let out = options.remove("out");
 
-        canvas.set_draw_color(Color::RGB(20, 40, 20));
-        canvas.clear();
+    let combos = sweep::combinations(&options)?;
+    let outcomes = sweep::run(layout, combos, frames, threads)?;
 
-        let road_ns = Road::new(350, 0, 100, 800, true); // North‑South road 
-        let road_ew = Road::new(0, 350, 800, 100, false); // East‑West road 
-        road_ns.draw(&mut canvas);
-        road_ew.draw(&mut canvas);
-        intersection::draw(&mut canvas);
-        light_n.draw(&mut canvas);
-        light_s.draw(&mut canvas);
-        light_e.draw(&mut canvas);
-        light_w.draw(&mut canvas);
-        for vehicle in &vehicles {
-            vehicle.draw(&mut canvas);
+    let names: Vec<&String> = options.keys().collect::<BTreeSet<_>>().into_iter().collect();
+    let mut table = String::new();
+    for name in &names {
+        table += &format!("{},", name);
+    }
+    table += "vehicles,stops_per_vehicle,average_travel_time,average_delay,fuel_per_vehicle,";
+    table += "equipped_stops_per_vehicle,unequipped_stops_per_vehicle,";
+    table += "equipped_fuel_per_vehicle,unequipped_fuel_per_vehicle,safety_events\n";
+    for outcome in outcomes {
+        for name in &names {
+            // Turn ratios contain commas
+            table += &format!("\"{}\",", outcome.options[*name]);
         }
-        canvas.present();
-
-        std::thread::sleep(Duration::from_millis(16));
+        table += &format!(
+            "{},{:.3},{:.2},{:.2},{:.2},{:.3},{:.3},{:.2},{:.2},{}\n",
+            outcome.totals.vehicles,
+            outcome.totals.stops_per_vehicle(),
+            outcome.totals.average_travel_time(),
+            outcome.totals.average_delay(),
+            outcome.totals.fuel_per_vehicle(),
+            outcome.equipped.stops_per_vehicle(),
+            outcome.unequipped.stops_per_vehicle(),
+            outcome.equipped.fuel_per_vehicle(),
+            outcome.unequipped.fuel_per_vehicle(),
+            outcome.safety_events
+        );
     }
-    Ok(())
-}
-
-fn is_safe_to_spawn(
-    vehicles: &[Vehicle],
-    direction: Direction,
-    last_spawn: &HashMap<Direction, i32>,
-    current_frame: i32,
-) -> bool {
-    if let Some(last_frame) = last_spawn.get(&direction) {
-        if current_frame - last_frame < SAFE_DISTANCE / 2 {
-            return false;
+    match out {
+        Some(path) => std::fs::write(&path, table).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            print!("{}", table);
+            Ok(())
         }
     }
+}
 
-    let (spawn_coord, is_vertical) = match direction {
-        Direction::North => (760, true),
-        Direction::South => (80, true), 
-        Direction::East => (80, false), 
-        Direction::West => (760, false),
+// Measures the saturation flow of each approach of an isolated intersection
+// and reports it with the capacity it gives, see `calibration::measure`. Takes
+// the scenario options, plus `--cycles` (default 5) queues to time per
+// approach and `--field-flow`, a saturation flow in vehicles per hour observed
+// on the street, to get the `--safe-distance` that would reproduce it.
+fn run_calibrate(mut options: HashMap<String, String>) -> Result<(), String> {
+    let cycles = match options.remove("cycles") {
+        Some(cycles) => cycles.parse().map_err(|_| format!("invalid cycles: {}", cycles))?,
+        None => 5,
+    };
+    let field_flow: Option<f32> = match options.remove("field-flow") {
+        Some(flow) => Some(flow.parse().map_err(|_| format!("invalid field flow: {}", flow))?),
+        None => None,
     };
+    let seed = match options.get("seed") {
+        Some(seed) => seed.parse().map_err(|_| format!("invalid seed: {}", seed))?,
+        None => 0,
+    };
+    let safe_distance = scenario::safe_distance(&options)?;
 
-    // Check distance from existing vehicles in the same direction
-    for vehicle in vehicles.iter().filter(|v| v.direction == direction) {
-        let vehicle_pos = if is_vertical {
-            vehicle.rect.y() + vehicle.rect.height() as i32
-        } else {
-            vehicle.rect.x() + vehicle.rect.width() as i32
+    let groups = calibration::measure(&options, seed, cycles)?;
+    let mut table = String::from("approach,saturation_headway,saturation_flow,start_up_lost_time,green_share,capacity");
+    if field_flow.is_some() {
+        table += ",safe_distance_for_field_flow";
+    }
+    table += "\n";
+    let seconds = |frames: f32| frames / FRAMES_PER_SECOND as f32;
+    for group in &groups {
+        let (Some(headway), Some(flow), Some(lost), Some(capacity)) = (
+            group.saturation_headway(),
+            group.saturation_flow(),
+            group.start_up_lost_time(),
+            group.capacity(),
+        ) else {
+            return Err(format!("queue heading {:?} too short to reach saturation", group.direction));
         };
-
-        let distance = (vehicle_pos - spawn_coord).abs();
-        if distance < SAFE_DISTANCE {
-            return false;
+        table += &format!(
+            "{:?},{:.2},{:.0},{:.2},{:.2},{:.0}",
+            group.direction,
+            seconds(headway),
+            flow,
+            seconds(lost),
+            group.green_share,
+            capacity
+        );
+        if let Some(flow) = field_flow {
+            table += &format!(",{}", calibration::safe_distance_for(headway, safe_distance, flow));
         }
+        table += "\n";
     }
-
-    true
+    print!("{}", table);
+    Ok(())
 }
 
-// Function to check if a vehicle is still on screen
-fn is_on_screen(vehicle: &Vehicle) -> bool {
-    let rect = vehicle.rect;
-    match vehicle.direction {
-        Direction::North => rect.y() > -50,
-        Direction::South => rect.y() < 850,
-        Direction::East => rect.x() < 850,
-        Direction::West => rect.x() > -50,
+// Safety events only go out when asked for with `--log-events <file>`
+fn log_events(log: &mut Option<BufWriter<File>>, events: Vec<SafetyEvent>) -> Result<(), String> {
+    if let Some(log) = log {
+        for event in events {
+            writeln!(log, "{}", event).map_err(|e| e.to_string())?;
+        }
     }
+    Ok(())
 }
 
-fn get_timer(vehicles: &[Vehicle], direction: Direction) -> f32 {
-    let mut cars_count = 0;
-    for car in vehicles {
-        if !car.has_turned && !car.in_intersection && car.direction == direction {
-            cars_count += 1;
+// Separates `--name value` pairs from the positional layout arguments
+fn split_options(args: &[String]) -> Result<(Vec<String>, HashMap<String, String>), String> {
+    let mut positional = Vec::new();
+    let mut options = HashMap::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(name) = arg.strip_prefix("--") {
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", name))?;
+            options.insert(name.to_string(), value.clone());
+        } else {
+            positional.push(arg.clone());
         }
     }
-    (cars_count as f32 * 1.55) / (SINGLE_ROAD_PART / (VEHICULE_LENGTH + SAFE_DISTANCE)) as f32
+    Ok((positional, options))
 }
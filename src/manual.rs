@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use crate::conflict_monitor;
+use crate::intersection::Controller;
+use crate::vehicle::Direction;
+
+// All-red time, in frames, between a light turning red and a conflicting one turning green
+pub const CLEARANCE_FRAMES: i32 = 90;
+
+fn conflicts(a: Direction, b: Direction) -> bool {
+    !conflict_monitor::approaches_compatible(a, b)
+}
+
+/// Lights set by an operator. Greens that conflict with a live green are
+/// refused; others wait until the clearance interval after every conflicting
+/// light went red has run.
+pub struct ManualControl {
+    // Controller to hand the lights back to when the operator is done
+    pub previous: Box<Controller>,
+    greens: Vec<Direction>,
+    waiting: Vec<Direction>,
+    red_since: HashMap<Direction, i32>,
+}
+
+impl ManualControl {
+    /// Takes over from `previous`, keeping `greens` green.
+    pub fn new(previous: Controller, greens: Vec<Direction>, frame: i32) -> Self {
+        // A light may have turned red just now, so start every clearance afresh
+        let red_since = [Direction::North, Direction::South, Direction::East, Direction::West]
+            .into_iter()
+            .filter(|d| !greens.contains(d))
+            .map(|d| (d, frame))
+            .collect();
+        ManualControl {
+            previous: Box::new(previous),
+            greens,
+            waiting: Vec::new(),
+            red_since,
+        }
+    }
+
+    pub fn greens(&self) -> &[Direction] {
+        &self.greens
+    }
+
+    /// Approaches turned on by the operator and waiting for their clearance.
+    pub fn waiting(&self) -> &[Direction] {
+        &self.waiting
+    }
+
+    /// The operator flips the light of `direction`: green turns red at once, a
+    /// waiting request is cancelled, and red asks for green.
+    pub fn toggle(&mut self, direction: Direction, frame: i32) -> Result<(), String> {
+        if let Some(i) = self.greens.iter().position(|&d| d == direction) {
+            self.greens.remove(i);
+            self.red_since.insert(direction, frame);
+            return Ok(());
+        }
+        if let Some(i) = self.waiting.iter().position(|&d| d == direction) {
+            self.waiting.remove(i);
+            return Ok(());
+        }
+        if let Some(&green) = self.greens.iter().find(|&&d| conflicts(d, direction)) {
+            return Err(format!("{:?} refused: conflicts with {:?}, which is green", direction, green));
+        }
+        self.waiting.push(direction);
+        Ok(())
+    }
+
+    /// Grants the waiting greens whose clearance has run.
+    pub fn update(&mut self, frame: i32) {
+        let mut i = 0;
+        while i < self.waiting.len() {
+            let direction = self.waiting[i];
+            let blocked = self.greens.iter().any(|&d| conflicts(d, direction))
+                || self.red_since.iter().any(|(&d, &since)| {
+                    conflicts(d, direction) && frame - since < CLEARANCE_FRAMES
+                });
+            if blocked {
+                i += 1;
+            } else {
+                self.waiting.remove(i);
+                self.greens.push(direction);
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::simulation::FRAMES_PER_SECOND;
+use crate::vehicle::{Direction, Vehicle};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TripTotals {
+    pub vehicles: u32,
+    pub stops: u32,
+    pub travel_frames: i64,
+    pub delay_frames: i64,
+    // Litres and grams, see `Emissions`
+    pub fuel: f64,
+    pub co2: f64,
+}
+
+impl TripTotals {
+    pub fn stops_per_vehicle(&self) -> f32 {
+        if self.vehicles == 0 {
+            return 0.;
+        }
+        self.stops as f32 / self.vehicles as f32
+    }
+
+    /// Mean time between entering and leaving the world, in seconds.
+    pub fn average_travel_time(&self) -> f32 {
+        if self.vehicles == 0 {
+            return 0.;
+        }
+        self.travel_frames as f32 / self.vehicles as f32 / FRAMES_PER_SECOND as f32
+    }
+
+    /// Mean fuel burnt, in millilitres.
+    pub fn fuel_per_vehicle(&self) -> f64 {
+        if self.vehicles == 0 {
+            return 0.;
+        }
+        self.fuel * 1000. / self.vehicles as f64
+    }
+
+    /// Mean time spent standing still, in seconds.
+    pub fn average_delay(&self) -> f32 {
+        if self.vehicles == 0 {
+            return 0.;
+        }
+        self.delay_frames as f32 / self.vehicles as f32 / FRAMES_PER_SECOND as f32
+    }
+}
+
+/// Totals for vehicles that have left the network, per entry direction,
+/// and the fuel burnt across the whole network.
+#[derive(Default)]
+pub struct Metrics {
+    pub total: TripTotals,
+    pub by_origin: HashMap<Direction, TripTotals>,
+    // Vehicles that did and did not receive signal timing
+    pub equipped: TripTotals,
+    pub unequipped: TripTotals,
+    // Litres and grams burnt by every vehicle so far, including those still travelling
+    pub fuel: f64,
+    pub co2: f64,
+}
+
+impl Metrics {
+    pub fn record(&mut self, vehicle: &Vehicle, frame: i32) {
+        let equipment = if vehicle.equipped { &mut self.equipped } else { &mut self.unequipped };
+        for totals in [&mut self.total, self.by_origin.entry(vehicle.origin).or_default(), equipment] {
+            totals.vehicles += 1;
+            totals.stops += vehicle.stops;
+            totals.travel_frames += (frame - vehicle.spawned_at) as i64;
+            totals.delay_frames += vehicle.delay_frames as i64;
+            totals.fuel += vehicle.emissions.fuel;
+            totals.co2 += vehicle.emissions.co2;
+        }
+    }
+}
+
+impl fmt::Display for TripTotals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} vehicles, {:.2} stops/vehicle, {:.1}s average travel time, {:.1}s average delay, \
+             {:.2} L fuel ({:.1} ml/vehicle), {:.2} kg CO2",
+            self.vehicles,
+            self.stops_per_vehicle(),
+            self.average_travel_time(),
+            self.average_delay(),
+            self.fuel,
+            self.fuel_per_vehicle(),
+            self.co2 / 1000.
+        )
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "completed {}", self.total)?;
+        for direction in [Direction::North, Direction::South, Direction::East, Direction::West] {
+            if let Some(totals) = self.by_origin.get(&direction) {
+                write!(f, "\n  {:?}bound: {}", direction, totals)?;
+            }
+        }
+        write!(
+            f,
+            "\n  network: {:.2} L fuel, {:.2} kg CO2 including vehicles still travelling",
+            self.fuel,
+            self.co2 / 1000.
+        )?;
+        if self.equipped.vehicles > 0 {
+            write!(f, "\n  equipped: {}", self.equipped)?;
+            write!(f, "\n  unequipped: {}", self.unequipped)?;
+        }
+        Ok(())
+    }
+}
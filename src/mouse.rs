@@ -0,0 +1,181 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::HashMap;
+
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::intersection::Controller;
+use crate::network::Network;
+use crate::simulation::{Simulation, FRAMES_PER_SECOND};
+use crate::vehicle::{Direction, Turn};
+
+// How close to the window edge a drag must start to feed an entry
+const EDGE: i32 = 60;
+// Movement, in pixels, that turns a press into a drag
+const DRAG_DISTANCE: i32 = 20;
+
+/// Mouse interaction with the simulation view: click a vehicle to inspect
+/// it, click a light to force its approach green (click it again to hand the
+/// intersection back to its controller), drag in from an edge to spawn a
+/// stream of vehicles for as long as the button is held.
+#[derive(Default)]
+pub struct Mouse {
+    pub selected: Option<u32>,
+    pressed_at: Option<(i32, i32)>,
+    stream: Option<(Direction, usize)>,
+    // Controllers set aside while their intersection is forced by hand
+    suspended: HashMap<usize, Controller>,
+}
+
+impl Mouse {
+    pub fn press(&mut self, sim: &mut Simulation, x: i32, y: i32) {
+        for intersection in &mut sim.intersections {
+            let Some(direction) = intersection.light_at(x, y) else {
+                continue;
+            };
+            let node = intersection.node;
+            if intersection.green_direction() == Some(direction) && self.suspended.contains_key(&node) {
+                intersection.controller = self.suspended.remove(&node).unwrap();
+            } else {
+                self.suspended
+                    .entry(node)
+                    .or_insert_with(|| std::mem::replace(&mut intersection.controller, Controller::External));
+                intersection.set_green(Some(direction));
+            }
+            return;
+        }
+
+        self.selected = sim
+            .vehicles
+            .iter()
+            .find(|v| v.rect.contains_point((x, y)))
+            .map(|v| v.id);
+        if self.selected.is_none() {
+            self.pressed_at = Some((x, y));
+        }
+    }
+
+    pub fn drag(&mut self, network: &Network, x: i32, y: i32) {
+        let Some((x0, y0)) = self.pressed_at else {
+            return;
+        };
+        if self.stream.is_none() && (x - x0).abs().max((y - y0).abs()) >= DRAG_DISTANCE {
+            self.stream = entry_at(network, x0, y0);
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.pressed_at = None;
+        self.stream = None;
+    }
+
+    /// Feeds the dragged entry and forgets vehicles that have left; call once per frame.
+    pub fn update(&mut self, sim: &mut Simulation) {
+        if let Some((direction, node)) = self.stream {
+            sim.spawn_at(direction, node);
+        }
+        if let Some(id) = self.selected {
+            if !sim.vehicles.iter().any(|v| v.id == id) {
+                self.selected = None;
+            }
+        }
+    }
+
+    /// Outlines the selected vehicle with a box describing it beside it.
+    pub fn draw(&self, canvas: &mut Canvas<Window>, sim: &Simulation) {
+        let Some(v) = self.selected.and_then(|id| sim.vehicles.iter().find(|v| v.id == id)) else {
+            return;
+        };
+        canvas.set_draw_color(Color::RGB(255, 230, 0));
+        let r = v.rect;
+        let _ = canvas.draw_rect(Rect::new(r.x() - 3, r.y() - 3, r.width() + 6, r.height() + 6));
+
+        let mut flags = Vec::new();
+        if v.in_intersection {
+            flags.push("IN BOX");
+        }
+        if v.has_turned {
+            flags.push("TURNED");
+        }
+        if v.stopped_frames > 0 {
+            flags.push("STOPPED");
+        }
+        if flags.is_empty() {
+            flags.push("MOVING");
+        }
+        if v.advised_speed.is_some() {
+            flags.push("ADVISED");
+        } else if v.equipped {
+            flags.push("CONNECTED");
+        }
+        let lines = [
+            format!("VEHICLE {}", v.id),
+            format!("HEADING {:?}", v.direction),
+            format!(
+                "TURN {}",
+                match v.turn {
+                    Turn::Straight => "STRAIGHT",
+                    Turn::Right => "RIGHT",
+                    Turn::Left => "LEFT",
+                }
+            ),
+            flags.join(" "),
+            format!("DELAY {:.1}S", v.delay_frames as f32 / FRAMES_PER_SECOND as f32),
+            format!("STOPS {}", v.stops),
+            format!("FUEL {:.1}ML", v.emissions.fuel * 1000.),
+            format!(
+                "REACT {:.1}S GAP {:.1}S",
+                v.driver.reaction_frames as f32 / FRAMES_PER_SECOND as f32,
+                v.driver.acceptable_gap as f32 / FRAMES_PER_SECOND as f32
+            ),
+        ];
+
+        let line_height = GLYPH_HEIGHT + 4;
+        let width = lines.iter().map(|l| font::text_width(l, 1)).max().unwrap_or(0) + 10;
+        let height = lines.len() as i32 * line_height + 6;
+        // Beside the vehicle, flipped to its left near the right edge of the map
+        let mut x = r.right() + 8;
+        if x + width > sim.network.width {
+            x = r.left() - 8 - width;
+        }
+        let y = r.top().clamp(0, (sim.network.height - height).max(0));
+        canvas.set_draw_color(Color::RGB(20, 20, 20));
+        let _ = canvas.fill_rect(Rect::new(x, y, width as u32, height as u32));
+        canvas.set_draw_color(Color::RGB(255, 230, 0));
+        let _ = canvas.draw_rect(Rect::new(x, y, width as u32, height as u32));
+        canvas.set_draw_color(Color::RGB(230, 230, 230));
+        for (i, line) in lines.iter().enumerate() {
+            font::draw_text(canvas, x + 5, y + 5 + i as i32 * line_height, 1, line);
+        }
+    }
+}
+
+// The boundary entry whose road meets the window edge near `(x, y)`
+fn entry_at(network: &Network, x: i32, y: i32) -> Option<(Direction, usize)> {
+    let (direction, along) = if y >= network.height - EDGE {
+        (Direction::North, x)
+    } else if y < EDGE {
+        (Direction::South, x)
+    } else if x < EDGE {
+        (Direction::East, y)
+    } else if x >= network.width - EDGE {
+        (Direction::West, y)
+    } else {
+        return None;
+    };
+    network
+        .entry_nodes(direction)
+        .into_iter()
+        .map(|node| {
+            let (cx, cy) = network.nodes[node].center;
+            let offset = match direction {
+                Direction::North | Direction::South => along - cx,
+                Direction::East | Direction::West => along - cy,
+            };
+            (node, offset.abs())
+        })
+        .filter(|&(_, offset)| offset <= 50)
+        .min_by_key(|&(_, offset)| offset)
+        .map(|(node, _)| (direction, node))
+}
@@ -0,0 +1,185 @@
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::road::Road;
+use crate::vehicle::Direction;
+
+// Distance between the centres of two neighbouring intersections
+pub const BLOCK_SPACING: i32 = 250;
+// Distance between the window edge and the first/last intersection centre
+pub const EDGE_MARGIN: i32 = 300;
+
+pub struct Node {
+    pub id: usize,
+    pub center: (i32, i32),
+    pub col: usize,
+    pub row: usize,
+}
+
+/// A road segment travelled in `direction`. `None` ends are on the network
+/// boundary (vehicles enter or leave the world there).
+pub struct Link {
+    pub from: Option<usize>,
+    pub to: Option<usize>,
+    pub direction: Direction,
+}
+
+pub struct Network {
+    pub nodes: Vec<Node>,
+    pub links: Vec<Link>,
+    pub cols: usize,
+    pub rows: usize,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Network {
+    /// The original layout: one intersection in the middle of an 800x800 world.
+    pub fn single() -> Self {
+        Network::build(1, 1, 400)
+    }
+
+    /// One intersection with approaches `approach` pixels long, from the
+    /// window edge to the stop line.
+    pub fn isolated(approach: i32) -> Self {
+        Network::build(1, 1, approach + 50)
+    }
+
+    /// East-West arterial with `count` signalized intersections.
+    pub fn corridor(count: usize) -> Self {
+        Network::build(count.max(1), 1, EDGE_MARGIN)
+    }
+
+    pub fn grid(cols: usize, rows: usize) -> Self {
+        Network::build(cols.max(1), rows.max(1), EDGE_MARGIN)
+    }
+
+    fn build(cols: usize, rows: usize, margin: i32) -> Self {
+        let mut nodes = Vec::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                nodes.push(Node {
+                    id: row * cols + col,
+                    center: (
+                        margin + col as i32 * BLOCK_SPACING,
+                        margin + row as i32 * BLOCK_SPACING,
+                    ),
+                    col,
+                    row,
+                });
+            }
+        }
+
+        let mut network = Network {
+            nodes,
+            links: Vec::new(),
+            cols,
+            rows,
+            width: 2 * margin + (cols as i32 - 1) * BLOCK_SPACING,
+            height: 2 * margin + (rows as i32 - 1) * BLOCK_SPACING,
+        };
+
+        let mut links = Vec::new();
+        for direction in [Direction::North, Direction::South, Direction::East, Direction::West] {
+            for node in &network.nodes {
+                let to = network.next_node(node.id, direction);
+                links.push(Link {
+                    from: Some(node.id),
+                    to,
+                    direction,
+                });
+                if network.previous_node(node.id, direction).is_none() {
+                    links.push(Link {
+                        from: None,
+                        to: Some(node.id),
+                        direction,
+                    });
+                }
+            }
+        }
+        network.links = links;
+        network
+    }
+
+    fn node_at(&self, col: isize, row: isize) -> Option<usize> {
+        if col < 0 || row < 0 || col as usize >= self.cols || row as usize >= self.rows {
+            return None;
+        }
+        Some(row as usize * self.cols + col as usize)
+    }
+
+    /// The intersection a vehicle reaches after leaving `node` heading `direction`.
+    pub fn next_node(&self, node: usize, direction: Direction) -> Option<usize> {
+        let n = &self.nodes[node];
+        let (col, row) = (n.col as isize, n.row as isize);
+        match direction {
+            Direction::North => self.node_at(col, row - 1),
+            Direction::South => self.node_at(col, row + 1),
+            Direction::East => self.node_at(col + 1, row),
+            Direction::West => self.node_at(col - 1, row),
+        }
+    }
+
+    pub fn previous_node(&self, node: usize, direction: Direction) -> Option<usize> {
+        let opposite = match direction {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        };
+        self.next_node(node, opposite)
+    }
+
+    /// Intersections fed directly from the boundary for vehicles heading `direction`.
+    pub fn entry_nodes(&self, direction: Direction) -> Vec<usize> {
+        self.links
+            .iter()
+            .filter(|l| l.from.is_none() && l.direction == direction)
+            .filter_map(|l| l.to)
+            .collect()
+    }
+
+    /// The link leaving `node` heading `direction` (towards the next node or out of the world).
+    pub fn outgoing_link(&self, node: usize, direction: Direction) -> usize {
+        self.links
+            .iter()
+            .position(|l| l.from == Some(node) && l.direction == direction)
+            .expect("every node has a link in each direction")
+    }
+
+    /// The boundary link feeding `node` with vehicles heading `direction`, if any.
+    pub fn entry_link(&self, node: usize, direction: Direction) -> Option<usize> {
+        self.links
+            .iter()
+            .position(|l| l.from.is_none() && l.to == Some(node) && l.direction == direction)
+    }
+
+    /// Links leaving the world, i.e. the possible destinations.
+    pub fn exit_links(&self) -> Vec<usize> {
+        (0..self.links.len()).filter(|&i| self.links[i].to.is_none()).collect()
+    }
+
+    /// Coordinate (along the direction of travel) of the boundary a vehicle
+    /// heading `direction` enters from.
+    pub fn entry_edge(&self, direction: Direction) -> i32 {
+        match direction {
+            Direction::North => self.height,
+            Direction::South => 0,
+            Direction::East => 0,
+            Direction::West => self.width,
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+        for col in 0..self.cols {
+            let x = self.nodes[col].center.0;
+            let road = Road::new(x - 50, 0, 100, self.height as u32, true);
+            road.draw(canvas);
+        }
+        for row in 0..self.rows {
+            let y = self.nodes[row * self.cols].center.1;
+            let road = Road::new(0, y - 50, self.width as u32, 100, false);
+            road.draw(canvas);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::network::Network;
+use crate::vehicle::{turned_direction, Direction, Turn, Vehicle, VELOCITY};
+
+// Shortest green before the controller reconsiders which approach to serve
+const MIN_GREEN_FRAMES: i32 = 120;
+// A vehicle stopped this long gets its approach served ahead of any score
+const MAX_WAIT_FRAMES: i32 = 480;
+
+const APPROACHES: [Direction; 4] = [Direction::South, Direction::West, Direction::North, Direction::East];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueuePolicy {
+    // Serve the approach whose queue most exceeds the queues it discharges into
+    MaxPressure,
+    // Serve the approach with the most vehicles on it
+    LongestQueue,
+}
+
+/// Picks the green approach from queue lengths instead of a fixed rotation.
+/// Once the minimum green has run, the green moves whenever another approach
+/// scores strictly higher than the one being served, or when a vehicle has
+/// waited too long on a red.
+pub struct QueueControl {
+    pub policy: QueuePolicy,
+    // Link leaving the intersection for each heading
+    exits: Vec<(Direction, usize)>,
+    current: Option<Direction>,
+    green_since: i32,
+}
+
+impl QueueControl {
+    pub fn new(policy: QueuePolicy, network: &Network, node: usize) -> Self {
+        QueueControl {
+            policy,
+            exits: APPROACHES
+                .iter()
+                .map(|&d| (d, network.outgoing_link(node, d)))
+                .collect(),
+            current: None,
+            green_since: 0,
+        }
+    }
+
+    /// The approach to give the green to on this frame, if any.
+    pub fn update(&mut self, node: usize, vehicles: &[Vehicle], frame: i32) -> Option<Direction> {
+        if self.current.is_some() && frame - self.green_since < MIN_GREEN_FRAMES {
+            return self.current;
+        }
+        let starved = APPROACHES
+            .into_iter()
+            .filter(|&d| Some(d) != self.current)
+            .map(|d| (d, longest_wait(vehicles, node, d)))
+            .filter(|&(_, wait)| wait >= MAX_WAIT_FRAMES)
+            .max_by_key(|&(_, wait)| wait);
+        if let Some((direction, _)) = starved {
+            self.current = Some(direction);
+            self.green_since = frame;
+            return self.current;
+        }
+
+        let score = |d: Direction| self.score(node, vehicles, d);
+        let best = APPROACHES
+            .into_iter()
+            .filter(|&d| approaching(vehicles, node, d, None) > 0)
+            .max_by_key(|&d| score(d));
+        if let Some(best) = best {
+            let keep = self.current.is_some_and(|current| score(current) >= score(best));
+            if !keep {
+                self.current = Some(best);
+                self.green_since = frame;
+            }
+        }
+        self.current
+    }
+
+    fn score(&self, node: usize, vehicles: &[Vehicle], direction: Direction) -> i32 {
+        match self.policy {
+            QueuePolicy::LongestQueue => approaching(vehicles, node, direction, None),
+            QueuePolicy::MaxPressure => [Turn::Straight, Turn::Right, Turn::Left]
+                .into_iter()
+                .map(|turn| {
+                    let upstream = approaching(vehicles, node, direction, Some(turn));
+                    if upstream == 0 {
+                        return 0;
+                    }
+                    let heading = turned_direction(direction, turn);
+                    let exit = self.exits.iter().find(|(d, _)| *d == heading).map(|(_, l)| *l);
+                    let downstream = vehicles.iter().filter(|v| v.link == exit).count() as i32;
+                    upstream - downstream
+                })
+                .sum(),
+        }
+    }
+}
+
+fn longest_wait(vehicles: &[Vehicle], node: usize, direction: Direction) -> i32 {
+    vehicles
+        .iter()
+        .filter(|v| v.node == node && !v.has_turned && !v.in_intersection && v.direction == direction)
+        .map(|v| v.stopped_frames)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Vehicles on the approach to the box of `node` heading `direction`,
+/// moving or not, optionally only those about to make `turn`.
+pub fn approaching(vehicles: &[Vehicle], node: usize, direction: Direction, turn: Option<Turn>) -> i32 {
+    approach(vehicles, node, direction, turn).count() as i32
+}
+
+/// Vehicles standing in the queue for the box of `node` heading `direction`,
+/// optionally only those about to make `turn`.
+pub fn queue_length(vehicles: &[Vehicle], node: usize, direction: Direction, turn: Option<Turn>) -> i32 {
+    approach(vehicles, node, direction, turn)
+        .filter(|v| v.stopped_frames > 0)
+        .count() as i32
+}
+
+/// Frames until each vehicle still driving in on the approach to the box of
+/// `node` heading `direction` reaches it, at full speed.
+pub fn arrivals(vehicles: &[Vehicle], node: usize, direction: Direction) -> Vec<i32> {
+    approach(vehicles, node, direction, None)
+        .filter(|v| v.stopped_frames == 0)
+        .map(|v| v.distance_to_box().max(0) / VELOCITY + 1)
+        .collect()
+}
+
+fn approach(
+    vehicles: &[Vehicle],
+    node: usize,
+    direction: Direction,
+    turn: Option<Turn>,
+) -> impl Iterator<Item = &Vehicle> {
+    vehicles
+        .iter()
+        .filter(move |v| v.node == node && !v.has_turned && !v.in_intersection && v.direction == direction)
+        .filter(move |v| turn.is_none_or(|turn| v.turn == turn))
+}
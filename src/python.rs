@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::intersection::Controller;
+use crate::metrics::TripTotals;
+use crate::scenario;
+use crate::simulation::Simulation;
+use crate::traffic_light::LightState;
+use crate::vehicle::{Direction, Turn, Vehicle};
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+fn heading_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::North => "north",
+        Direction::South => "south",
+        Direction::East => "east",
+        Direction::West => "west",
+    }
+}
+
+fn parse_heading(name: &str) -> PyResult<Direction> {
+    HEADINGS
+        .into_iter()
+        .find(|&d| heading_name(d) == name)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown heading: {}", name)))
+}
+
+fn totals(totals: &TripTotals) -> HashMap<&'static str, f64> {
+    HashMap::from([
+        ("vehicles", totals.vehicles as f64),
+        ("stops", totals.stops as f64),
+        ("stops_per_vehicle", totals.stops_per_vehicle() as f64),
+        ("average_travel_time", totals.average_travel_time() as f64),
+        ("average_delay", totals.average_delay() as f64),
+        ("fuel", totals.fuel),
+        ("co2", totals.co2),
+    ])
+}
+
+/// Snapshot of a vehicle, taken when `Simulation.vehicles` is read.
+#[pyclass(name = "Vehicle", frozen, get_all)]
+struct PyVehicle {
+    id: u32,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    heading: &'static str,
+    turn: &'static str,
+    origin: &'static str,
+    node: usize,
+    in_intersection: bool,
+    has_turned: bool,
+    stops: u32,
+}
+
+impl From<&Vehicle> for PyVehicle {
+    fn from(v: &Vehicle) -> Self {
+        PyVehicle {
+            id: v.id,
+            x: v.rect.x(),
+            y: v.rect.y(),
+            width: v.rect.width(),
+            height: v.rect.height(),
+            heading: heading_name(v.direction),
+            turn: match v.turn {
+                Turn::Straight => "straight",
+                Turn::Right => "right",
+                Turn::Left => "left",
+            },
+            origin: heading_name(v.origin),
+            node: v.node,
+            in_intersection: v.in_intersection,
+            has_turned: v.has_turned,
+            stops: v.stops,
+        }
+    }
+}
+
+/// A scenario, built from the same layout and options as the command line:
+/// `Simulation("grid 3 3", seed=1, demand=10, plan="max-pressure")`.
+#[pyclass(name = "Simulation", unsendable)]
+struct PySimulation {
+    sim: Simulation,
+}
+
+#[pymethods]
+impl PySimulation {
+    #[new]
+    #[pyo3(signature = (layout = "single", seed = 0, **options))]
+    fn new(layout: &str, seed: u64, options: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let layout: Vec<String> = layout.split_whitespace().map(String::from).collect();
+        let mut parsed = HashMap::new();
+        for (name, value) in options.into_iter().flatten() {
+            let name = name.str()?.to_string().replace('_', "-");
+            parsed.insert(name, value.str()?.to_string());
+        }
+        let sim = scenario::build(&layout, &parsed, seed).map_err(PyValueError::new_err)?;
+        Ok(PySimulation { sim })
+    }
+
+    /// Advances `frames` frames and returns the safety events raised meanwhile.
+    #[pyo3(signature = (frames = 1))]
+    fn step(&mut self, frames: u32) -> Vec<String> {
+        (0..frames)
+            .flat_map(|_| self.sim.step())
+            .map(|event| event.to_string())
+            .collect()
+    }
+
+    /// Adds a vehicle heading `heading` at a random entry, if there is room.
+    fn spawn(&mut self, heading: &str) -> PyResult<bool> {
+        Ok(self.sim.spawn(parse_heading(heading)?))
+    }
+
+    #[getter]
+    fn frame(&self) -> i32 {
+        self.sim.frame_count
+    }
+
+    #[getter]
+    fn intersections(&self) -> usize {
+        self.sim.intersections.len()
+    }
+
+    #[getter]
+    fn vehicles(&self) -> Vec<PyVehicle> {
+        self.sim.vehicles.iter().map(PyVehicle::from).collect()
+    }
+
+    /// `"green"` or `"red"` for each heading at intersection `node`.
+    fn lights(&self, node: usize) -> PyResult<HashMap<&'static str, &'static str>> {
+        let intersection = self
+            .sim
+            .intersections
+            .get(node)
+            .ok_or_else(|| PyIndexError::new_err(format!("no intersection {}", node)))?;
+        Ok(HEADINGS
+            .into_iter()
+            .map(|d| {
+                let state = match intersection.light_for(d) {
+                    LightState::Green => "green",
+                    LightState::Red => "red",
+                    LightState::FlashingRed => "flashing-red",
+                    LightState::FlashingYellow => "flashing-yellow",
+                };
+                (heading_name(d), state)
+            })
+            .collect())
+    }
+
+    /// Takes intersection `node` away from its controller and gives the green
+    /// to `heading`, or red to every approach when `heading` is `None`.
+    #[pyo3(signature = (node, heading = None))]
+    fn set_green(&mut self, node: usize, heading: Option<&str>) -> PyResult<()> {
+        let direction = heading.map(parse_heading).transpose()?;
+        let intersection = self
+            .sim
+            .intersections
+            .get_mut(node)
+            .ok_or_else(|| PyIndexError::new_err(format!("no intersection {}", node)))?;
+        intersection.controller = Controller::External;
+        intersection.set_green(direction);
+        Ok(())
+    }
+
+    /// Totals for vehicles that have left the network: `"total"` and one
+    /// entry per origin heading, plus `"network"` fuel and CO2 for every
+    /// vehicle so far.
+    fn metrics(&self) -> HashMap<&'static str, HashMap<&'static str, f64>> {
+        let metrics = &self.sim.metrics;
+        let mut result = HashMap::from([
+            ("total", totals(&metrics.total)),
+            ("network", HashMap::from([("fuel", metrics.fuel), ("co2", metrics.co2)])),
+        ]);
+        for (direction, by_origin) in &metrics.by_origin {
+            result.insert(heading_name(*direction), totals(by_origin));
+        }
+        result
+    }
+}
+
+#[pymodule]
+fn road_intersection(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySimulation>()?;
+    m.add_class::<PyVehicle>()?;
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::HashMap;
+
+use crate::traffic_light::LightState;
+use crate::vehicle::{Direction, Vehicle};
+
+// Distance before the box, in pixels, from which the first vehicle of an approach asks for a reservation
+const REQUEST_DISTANCE: i32 = 150;
+// Clearance kept around every reserved footprint, in pixels
+const MARGIN: i32 = 6;
+// Longest crossing planned, in frames
+const MAX_PLAN_FRAMES: usize = 300;
+
+// Where a granted vehicle will be on each frame from `start` until it has left the box
+struct Reservation {
+    start: i32,
+    path: Vec<Rect>,
+}
+
+impl Reservation {
+    fn at(&self, frame: i32) -> Option<Rect> {
+        usize::try_from(frame - self.start).ok().and_then(|i| self.path.get(i).copied())
+    }
+}
+
+/// Autonomous intersection management: no lights. The first vehicle of each
+/// approach asks, as it nears the box, for the space it will sweep through
+/// the box on every frame of its crossing. Requests that overlap nothing
+/// already granted are granted, first come first served; the others wait at
+/// the stop line and ask again on the next frame. A vehicle that falls behind
+/// its plan before entering loses its reservation and asks again.
+#[derive(Default)]
+pub struct ReservationManager {
+    granted: HashMap<u32, Reservation>,
+    frame: i32,
+}
+
+impl ReservationManager {
+    /// Checks the reservations of intersection `node` against where vehicles
+    /// actually are, then takes this frame's requests.
+    pub fn update(&mut self, node: usize, center: (i32, i32), vehicles: &[Vehicle], frame: i32, safe_distance: i32) {
+        self.frame = frame;
+        let square = box_rect(center);
+        let here: Vec<&Vehicle> = vehicles.iter().filter(|v| v.node == node).collect();
+        self.granted.retain(|id, _| here.iter().any(|v| v.id == *id));
+
+        // Vehicles show where they got to on the previous frame
+        for v in &here {
+            let on_plan = self.granted.get(&v.id).map(|r| r.at(frame - 1) == Some(v.rect));
+            if on_plan != Some(false) {
+                continue;
+            }
+            self.granted.remove(&v.id);
+            if v.in_intersection {
+                // Already committed: replan from where it is and move others out of its way
+                let reservation = plan(v, frame, square);
+                self.granted.retain(|id, other| {
+                    here.iter().any(|w| w.id == *id && w.in_intersection) || !conflicts(other, &reservation, square, safe_distance)
+                });
+                self.granted.insert(v.id, reservation);
+            }
+        }
+
+        let mut requests: Vec<&Vehicle> = [Direction::North, Direction::South, Direction::East, Direction::West]
+            .into_iter()
+            .filter_map(|d| {
+                here.iter()
+                    .copied()
+                    .filter(|v| v.direction == d && !v.in_intersection && !v.has_turned)
+                    .min_by_key(|v| v.distance_to_box())
+            })
+            .filter(|v| v.distance_to_box() < REQUEST_DISTANCE && !self.granted.contains_key(&v.id))
+            .collect();
+        // Closest to the box asks first
+        requests.sort_by_key(|v| (v.distance_to_box(), v.id));
+        for v in requests {
+            let reservation = plan(v, frame, square);
+            if !self.granted.values().any(|other| conflicts(other, &reservation, square, safe_distance)) {
+                self.granted.insert(v.id, reservation);
+            }
+        }
+    }
+
+    /// Green for vehicles holding a reservation or already in the box.
+    pub fn signal_for(&self, vehicle: &Vehicle) -> LightState {
+        if vehicle.in_intersection || self.granted.contains_key(&vehicle.id) {
+            LightState::Green
+        } else {
+            LightState::Red
+        }
+    }
+
+    /// Outlines the space reserved in the box on the current frame.
+    pub fn draw(&self, canvas: &mut Canvas<Window>, center: (i32, i32)) {
+        let square = box_rect(center);
+        canvas.set_draw_color(Color::RGB(0, 200, 255));
+        for reservation in self.granted.values() {
+            if let Some(rect) = reservation.at(self.frame).and_then(|r| r.intersection(square)) {
+                let _ = canvas.draw_rect(rect);
+            }
+        }
+    }
+}
+
+fn box_rect(center: (i32, i32)) -> Rect {
+    Rect::new(center.0 - 50, center.1 - 50, 100, 100)
+}
+
+// Drives a copy of `vehicle` through the box unhindered, from `frame` on
+fn plan(vehicle: &Vehicle, frame: i32, square: Rect) -> Reservation {
+    let mut v = vehicle.clone();
+    let mut path = Vec::new();
+    while path.len() < MAX_PLAN_FRAMES && !v.has_cleared_intersection() {
+        let before = v.rect;
+        v.update(LightState::Green);
+        v.track_motion(v.rect != before);
+        path.push(v.rect);
+        if v.in_intersection && !v.rect.has_intersection(square) {
+            break;
+        }
+    }
+    Reservation { start: frame, path }
+}
+
+// Whether two reservations put vehicles too close together in the box, or
+// one less than `safe_distance` behind the other in a lane, on some frame
+fn conflicts(a: &Reservation, b: &Reservation, square: Rect, safe_distance: i32) -> bool {
+    let margin = |r: Rect| {
+        let grow = 2 * MARGIN as u32;
+        Rect::new(r.x() - MARGIN, r.y() - MARGIN, r.width() + grow, r.height() + grow)
+    };
+    a.path.iter().enumerate().any(|(i, &ra)| {
+        let frame = a.start + i as i32;
+        // The frame either side too, for vehicles a frame off their plan
+        (frame - 1..=frame + 1).any(|f| {
+            b.at(f).is_some_and(|rb| {
+                following(ra, rb, safe_distance)
+                    || ra
+                        .intersection(square)
+                        .zip(rb.intersection(square))
+                        .is_some_and(|(ra, rb)| margin(ra).has_intersection(rb))
+            })
+        })
+    })
+}
+
+// Whether two footprints share a lane with less than `safe_distance` between them
+fn following(a: Rect, b: Rect, safe_distance: i32) -> bool {
+    let horizontal = |r: Rect| r.width() > r.height();
+    if horizontal(a) != horizontal(b) {
+        return false;
+    }
+    let (lateral, gap) = if horizontal(a) {
+        (a.y() < b.bottom() && b.y() < a.bottom(), a.x().max(b.x()) - a.right().min(b.right()))
+    } else {
+        (a.x() < b.right() && b.x() < a.right(), a.y().max(b.y()) - a.bottom().min(b.bottom()))
+    };
+    lateral && gap < safe_distance
+}
@@ -0,0 +1,25 @@
+use crate::vehicle::{turned_direction, Turn, Vehicle, FULL_STOP_FRAMES, VELOCITY};
+
+/// Whether `vehicle`, waiting at a red light, may make its right turn now:
+/// it has come to a full stop and the lane it turns into is clear.
+pub fn may_turn(vehicle: &Vehicle, vehicles: &[Vehicle]) -> bool {
+    if vehicle.turn != Turn::Right
+        || vehicle.in_intersection
+        || !vehicle.is_at_stop_line()
+        || vehicle.stopped_frames < FULL_STOP_FRAMES
+    {
+        return false;
+    }
+    let target = turned_direction(vehicle.direction, Turn::Right);
+    !vehicles
+        .iter()
+        .filter(|other| other.id != vehicle.id && other.node == vehicle.node && !other.has_turned)
+        .any(|other| {
+            // Traffic already crossing the box into the same lane
+            if other.in_intersection {
+                return other.direction == target || other.exit_direction() == target;
+            }
+            // Cross traffic approaching in that lane
+            other.direction == target && other.distance_to_box() < vehicle.driver.acceptable_gap * VELOCITY
+        })
+}
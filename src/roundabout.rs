@@ -0,0 +1,113 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::f32::consts::PI;
+
+use crate::vehicle::{Direction, Vehicle};
+
+// Radius of the circulating lane (its centre line) and of the central island
+pub const RADIUS: f32 = 40.;
+const ISLAND_RADIUS: i32 = 22;
+// Distance from the road axis to the middle of a lane
+const LANE_OFFSET: f32 = 25.;
+// Arc, in radians, kept free between two circulating vehicles
+pub const MIN_GAP: f32 = 1.25;
+// Arc upstream of an entry that must be clear of circulating traffic before
+// joining; it covers the drive from the yield line to the ring
+const YIELD_UPSTREAM: f32 = 2.6;
+const YIELD_DOWNSTREAM: f32 = 0.9;
+
+// Angles are counter-clockwise with north up, so traffic circulates with the
+// island on its left. `along` is the distance from the lane line to the ring.
+fn along() -> f32 {
+    (RADIUS * RADIUS - LANE_OFFSET * LANE_OFFSET).sqrt()
+}
+
+/// Where vehicles heading `direction` join the ring.
+pub fn entry_angle(direction: Direction) -> f32 {
+    let (x, y) = match direction {
+        Direction::North => (LANE_OFFSET, -along()),
+        Direction::South => (-LANE_OFFSET, along()),
+        Direction::East => (-along(), -LANE_OFFSET),
+        Direction::West => (along(), LANE_OFFSET),
+    };
+    y.atan2(x)
+}
+
+/// Where vehicles leaving heading `direction` peel off the ring.
+pub fn exit_angle(direction: Direction) -> f32 {
+    let (x, y) = match direction {
+        Direction::North => (LANE_OFFSET, along()),
+        Direction::South => (-LANE_OFFSET, -along()),
+        Direction::East => (along(), -LANE_OFFSET),
+        Direction::West => (-along(), LANE_OFFSET),
+    };
+    y.atan2(x)
+}
+
+/// Counter-clockwise arc from `from` to `to`, in `[0, 2π)`.
+pub fn arc(from: f32, to: f32) -> f32 {
+    (to - from).rem_euclid(2. * PI)
+}
+
+/// Screen position of the point of the ring at `angle`.
+pub fn point(center: (i32, i32), angle: f32) -> (i32, i32) {
+    (
+        center.0 + (RADIUS * angle.cos()).round() as i32,
+        center.1 - (RADIUS * angle.sin()).round() as i32,
+    )
+}
+
+/// Screen point where the lane of vehicles heading `direction` meets the ring.
+pub fn entry_point(center: (i32, i32), direction: Direction) -> (i32, i32) {
+    point(center, entry_angle(direction))
+}
+
+/// Heading of a vehicle circulating at `angle`.
+pub fn heading(angle: f32) -> Direction {
+    // Counter-clockwise tangent, converted to screen axes
+    let (dx, dy) = (-angle.sin(), -angle.cos());
+    if dx.abs() > dy.abs() {
+        if dx > 0. {
+            Direction::East
+        } else {
+            Direction::West
+        }
+    } else if dy < 0. {
+        Direction::North
+    } else {
+        Direction::South
+    }
+}
+
+/// Whether `vehicle`, waiting at its yield line, must give way to traffic
+/// already on the ring.
+pub fn must_yield(vehicle: &Vehicle, vehicles: &[Vehicle]) -> bool {
+    let entry = entry_angle(vehicle.direction);
+    vehicles
+        .iter()
+        .filter(|other| other.id != vehicle.id && other.node == vehicle.node)
+        .filter_map(|other| other.ring_angle)
+        .any(|angle| arc(angle, entry) < YIELD_UPSTREAM || arc(entry, angle) < YIELD_DOWNSTREAM)
+}
+
+pub fn draw(canvas: &mut Canvas<Window>, center: (i32, i32)) {
+    let (cx, cy) = center;
+    canvas.set_draw_color(Color::RGB(45, 45, 50));
+    let _ = canvas.fill_rect(Rect::new(cx - 50, cy - 50, 100, 100));
+
+    // Central island, filled one row at a time
+    canvas.set_draw_color(Color::RGB(40, 90, 40));
+    for dy in -ISLAND_RADIUS..=ISLAND_RADIUS {
+        let half = ((ISLAND_RADIUS * ISLAND_RADIUS - dy * dy) as f32).sqrt() as i32;
+        let _ = canvas.fill_rect(Rect::new(cx - half, cy + dy, (2 * half + 1) as u32, 1));
+    }
+
+    // Dashed centre line of the circulating lane
+    canvas.set_draw_color(Color::RGB(220, 220, 220));
+    for i in 0..24 {
+        let (x, y) = point(center, i as f32 * PI / 12.);
+        let _ = canvas.fill_rect(Rect::new(x - 1, y - 1, 3, 3));
+    }
+}
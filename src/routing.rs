@@ -0,0 +1,91 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::network::{Network, BLOCK_SPACING};
+use crate::vehicle::{turned_direction, Turn};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteChoice {
+    // No destination: a turn is drawn from the turn ratios at every intersection
+    TurnRatios,
+    // Fewest links between origin and destination
+    ShortestPath,
+    // Least total travel time, using link times measured so far
+    TravelTime,
+}
+
+pub struct Router {
+    pub choice: RouteChoice,
+    // Smoothed time, in frames, to travel each link and clear the intersection at its end
+    link_times: Vec<f32>,
+}
+
+impl Router {
+    pub fn new(network: &Network, choice: RouteChoice, velocity: i32) -> Self {
+        let free_flow = BLOCK_SPACING as f32 / velocity as f32;
+        Router {
+            choice,
+            link_times: vec![free_flow; network.links.len()],
+        }
+    }
+
+    /// Folds one observed traversal of `link` into its travel time estimate.
+    pub fn record(&mut self, link: usize, frames: i32) {
+        let time = &mut self.link_times[link];
+        *time = 0.9 * *time + 0.1 * frames as f32;
+    }
+
+    /// The turns to make at each intersection to go from the boundary link
+    /// `origin` to the exit link `destination`, or `None` if it can't be reached.
+    pub fn route(&self, network: &Network, origin: usize, destination: usize) -> Option<VecDeque<Turn>> {
+        let cost = |link: usize| -> u32 {
+            if network.links[link].to.is_none() {
+                return 0;
+            }
+            match self.choice {
+                RouteChoice::TravelTime => self.link_times[link].round() as u32,
+                _ => 1,
+            }
+        };
+
+        // Dijkstra over links; `previous` remembers the link and turn we came from
+        let mut best = vec![u32::MAX; network.links.len()];
+        let mut previous: Vec<Option<(usize, Turn)>> = vec![None; network.links.len()];
+        let mut heap = BinaryHeap::new();
+        best[origin] = 0;
+        heap.push(Reverse((0, origin)));
+
+        while let Some(Reverse((dist, link))) = heap.pop() {
+            if link == destination {
+                break;
+            }
+            if dist > best[link] {
+                continue;
+            }
+            let Some(node) = network.links[link].to else {
+                continue;
+            };
+            for turn in [Turn::Straight, Turn::Right, Turn::Left] {
+                let heading = turned_direction(network.links[link].direction, turn);
+                let next = network.outgoing_link(node, heading);
+                let next_dist = dist + cost(next);
+                if next_dist < best[next] {
+                    best[next] = next_dist;
+                    previous[next] = Some((link, turn));
+                    heap.push(Reverse((next_dist, next)));
+                }
+            }
+        }
+
+        if best[destination] == u32::MAX {
+            return None;
+        }
+        let mut turns = VecDeque::new();
+        let mut link = destination;
+        while let Some((from, turn)) = previous[link] {
+            turns.push_front(turn);
+            link = from;
+        }
+        Some(turns)
+    }
+}
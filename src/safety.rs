@@ -0,0 +1,152 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::traffic_light::LightState;
+use crate::vehicle::{Direction, Vehicle};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SafetyEvent {
+    Overlap { frame: i32, a: u32, b: u32 },
+    RedLightEntry { frame: i32, vehicle: u32, direction: Direction },
+    NoProgress { frame: i32, vehicle: u32, stalled_frames: i32 },
+    ConflictingGreen { frame: i32, node: usize, a: Direction, b: Direction },
+}
+
+impl fmt::Display for SafetyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SafetyEvent::Overlap { frame, a, b } => {
+                write!(f, "[frame {}] overlap: vehicle {} and vehicle {}", frame, a, b)
+            }
+            SafetyEvent::RedLightEntry { frame, vehicle, direction } => write!(
+                f,
+                "[frame {}] red light entry: vehicle {} heading {:?}",
+                frame, vehicle, direction
+            ),
+            SafetyEvent::NoProgress { frame, vehicle, stalled_frames } => write!(
+                f,
+                "[frame {}] no progress: vehicle {} stalled for {} frames",
+                frame, vehicle, stalled_frames
+            ),
+            SafetyEvent::ConflictingGreen { frame, node, a, b } => write!(
+                f,
+                "[frame {}] conflicting green: {:?} and {:?} at intersection {}, flashing red",
+                frame, a, b, node
+            ),
+        }
+    }
+}
+
+pub struct SafetyChecker {
+    stall_frames: i32,
+    overlapping: HashSet<(u32, u32)>,
+    in_intersection: HashSet<u32>,
+    red_entries: HashSet<u32>,
+    // Last known position and the frame it was first seen there
+    positions: HashMap<u32, ((i32, i32), i32)>,
+    stalled: HashSet<u32>,
+}
+
+impl SafetyChecker {
+    pub fn new(stall_frames: i32) -> Self {
+        SafetyChecker {
+            stall_frames,
+            overlapping: HashSet::new(),
+            in_intersection: HashSet::new(),
+            red_entries: HashSet::new(),
+            positions: HashMap::new(),
+            stalled: HashSet::new(),
+        }
+    }
+
+    /// Runs every invariant against the current tick and returns only the
+    /// violations that started on this frame.
+    pub fn check<F>(&mut self, vehicles: &[Vehicle], light_for: F, frame: i32) -> Vec<SafetyEvent>
+    where
+        F: Fn(&Vehicle) -> LightState,
+    {
+        let mut events = Vec::new();
+
+        let mut overlapping = HashSet::new();
+        for (i, a) in vehicles.iter().enumerate() {
+            for b in &vehicles[i + 1..] {
+                if a.rect.has_intersection(b.rect) {
+                    let pair = (a.id.min(b.id), a.id.max(b.id));
+                    if !self.overlapping.contains(&pair) {
+                        events.push(SafetyEvent::Overlap { frame, a: pair.0, b: pair.1 });
+                    }
+                    overlapping.insert(pair);
+                }
+            }
+        }
+        self.overlapping = overlapping;
+
+        let mut in_intersection = HashSet::new();
+        for v in vehicles.iter().filter(|v| v.in_intersection) {
+            // Direction is still the approach direction until the vehicle turns
+            if !self.in_intersection.contains(&v.id)
+                && !v.has_turned
+                && light_for(v) == LightState::Red
+            {
+                events.push(SafetyEvent::RedLightEntry {
+                    frame,
+                    vehicle: v.id,
+                    direction: v.direction,
+                });
+                self.red_entries.insert(v.id);
+            }
+            in_intersection.insert(v.id);
+        }
+        self.in_intersection = in_intersection;
+        self.red_entries.retain(|id| self.in_intersection.contains(id));
+
+        let mut positions = HashMap::new();
+        let mut stalled = HashSet::new();
+        for v in vehicles {
+            let pos = (v.rect.x(), v.rect.y());
+            // Queueing behind a red light is expected, not a lack of progress
+            let held_by_red = !v.in_intersection
+                && !v.has_turned
+                && light_for(v) == LightState::Red;
+            let since = match self.positions.get(&v.id) {
+                Some(&(last, since)) if last == pos && !held_by_red => since,
+                _ => frame,
+            };
+            let stalled_frames = frame - since;
+            if stalled_frames >= self.stall_frames {
+                if !self.stalled.contains(&v.id) {
+                    events.push(SafetyEvent::NoProgress { frame, vehicle: v.id, stalled_frames });
+                }
+                stalled.insert(v.id);
+            }
+            positions.insert(v.id, (pos, since));
+        }
+        self.positions = positions;
+        self.stalled = stalled;
+
+        events
+    }
+
+    fn is_flagged(&self, id: u32) -> bool {
+        self.red_entries.contains(&id)
+            || self.stalled.contains(&id)
+            || self.overlapping.iter().any(|&(a, b)| a == id || b == id)
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, vehicles: &[Vehicle]) {
+        canvas.set_draw_color(Color::RGB(255, 0, 255));
+        for v in vehicles.iter().filter(|v| self.is_flagged(v.id)) {
+            let marker = Rect::new(
+                v.rect.x() - 4,
+                v.rect.y() - 4,
+                v.rect.width() + 8,
+                v.rect.height() + 8,
+            );
+            let _ = canvas.draw_rect(marker);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::coordination::{self, FixedTimePlan};
+use crate::intersection::Controller;
+use crate::network::Network;
+use crate::pressure::QueuePolicy;
+use crate::reservation::ReservationManager;
+use crate::routing::RouteChoice;
+use crate::simulation::{Simulation, FRAMES_PER_SECOND, SAFE_DISTANCE};
+use crate::stop_control::StopControl;
+use crate::vehicle::{Direction, TurnRatios};
+use crate::webster::Webster;
+
+// Fixed-time greens, in frames, for the coordinated and the other approaches
+const MAIN_GREEN: i32 = 150;
+const MINOR_GREEN: i32 = 60;
+// Speed platoons are assumed to travel at when computing green wave offsets
+const PROGRESSION_SPEED: f32 = 5.;
+
+/// Builds the scenario described by the layout arguments (`single`,
+/// `corridor [count]`, `grid [cols] [rows]`) and `--name value` options.
+pub fn build(
+    layout: &[String],
+    options: &HashMap<String, String>,
+    seed: u64,
+) -> Result<Simulation, String> {
+    let network = parse_network(layout)?;
+    let mut sim = Simulation::new(network, seed);
+    configure(&mut sim, options)?;
+    Ok(sim)
+}
+
+/// Applies the `--name value` options to an already built simulation.
+pub fn configure(sim: &mut Simulation, options: &HashMap<String, String>) -> Result<(), String> {
+    match options.get("control").map(String::as_str) {
+        None | Some("signal") => {}
+        Some("roundabout") => sim.set_controller(|| Controller::Roundabout),
+        Some("all-way-stop") => sim.set_controller(|| Controller::StopSign(StopControl::all_way())),
+        Some("two-way-stop") => sim.set_controller(|| Controller::StopSign(StopControl::two_way())),
+        Some("reservation") => sim.set_controller(|| Controller::Reservation(ReservationManager::default())),
+        Some(other) => return Err(format!("unknown control: {}", other)),
+    }
+    match options.get("plan").map(String::as_str) {
+        None | Some("adaptive") => {}
+        Some("fixed") => {
+            let plan = FixedTimePlan::new(Direction::East, MAIN_GREEN, MINOR_GREEN);
+            let plans = vec![plan; sim.network.nodes.len()];
+            sim.set_plans(plans);
+        }
+        Some("green-wave") => {
+            let plans = coordination::green_wave(
+                &sim.network,
+                Direction::East,
+                PROGRESSION_SPEED,
+                MAIN_GREEN,
+                MINOR_GREEN,
+            );
+            sim.set_plans(plans);
+        }
+        Some("webster") => {
+            // Starts on the standard plan until there are flows to time it to
+            let plan = FixedTimePlan::new(Direction::East, MAIN_GREEN, MINOR_GREEN);
+            let plans = vec![plan; sim.network.nodes.len()];
+            sim.set_plans(plans);
+            sim.webster = Some(Webster::new(sim.network.nodes.len()));
+        }
+        Some("max-pressure") => sim.set_queue_control(QueuePolicy::MaxPressure),
+        Some("longest-queue") => sim.set_queue_control(QueuePolicy::LongestQueue),
+        Some(other) => return Err(format!("unknown plan: {}", other)),
+    }
+    if let Some(demand) = options.get("demand") {
+        sim.demand = demand.parse().map_err(|_| format!("invalid demand: {}", demand))?;
+    }
+    sim.safe_distance = safe_distance(options)?;
+    if let Some(turns) = options.get("turns") {
+        sim.turn_ratios = parse_turns(turns)?;
+    }
+    sim.right_turn_on_red = match options.get("rtor").map(String::as_str) {
+        None | Some("off") => false,
+        Some("on") => true,
+        Some(other) => return Err(format!("expected on or off for --rtor: {}", other)),
+    };
+    if let Some(share) = options.get("penetration") {
+        sim.penetration = match share.parse() {
+            Ok(share) if (0. ..=1.).contains(&share) => share,
+            _ => return Err(format!("expected a share from 0 to 1 for --penetration: {}", share)),
+        };
+    }
+    sim.varied_drivers = match options.get("drivers").map(String::as_str) {
+        None | Some("uniform") => false,
+        Some("varied") => true,
+        Some(other) => return Err(format!("expected uniform or varied for --drivers: {}", other)),
+    };
+    if let Some(window) = options.get("night-flash") {
+        sim.night_flash = Some(parse_window(window)?);
+    }
+    sim.router.choice = match options.get("routing").map(String::as_str) {
+        None | Some("turns") => RouteChoice::TurnRatios,
+        Some("shortest") => RouteChoice::ShortestPath,
+        Some("travel-time") => RouteChoice::TravelTime,
+        Some(other) => return Err(format!("unknown routing: {}", other)),
+    };
+    Ok(())
+}
+
+/// The `--safe-distance` the options ask for, or the default.
+pub fn safe_distance(options: &HashMap<String, String>) -> Result<i32, String> {
+    match options.get("safe-distance") {
+        Some(distance) => distance.parse().map_err(|_| format!("invalid safe distance: {}", distance)),
+        None => Ok(SAFE_DISTANCE),
+    }
+}
+
+// `start,end` in seconds, e.g. `120,300`, as a range of frames
+fn parse_window(s: &str) -> Result<Range<i32>, String> {
+    let invalid = || format!("invalid night flash window: {}", s);
+    let (start, end) = s.split_once(',').ok_or_else(invalid)?;
+    let seconds = |t: &str| t.trim().parse::<f32>().map_err(|_| invalid());
+    let (start, end) = (seconds(start)?, seconds(end)?);
+    if start > end {
+        return Err(invalid());
+    }
+    let frames = |t: f32| (t * FRAMES_PER_SECOND as f32) as i32;
+    Ok(frames(start)..frames(end))
+}
+
+// `straight,right,left` shares, e.g. `0.8,0.1,0.1`
+fn parse_turns(value: &str) -> Result<TurnRatios, String> {
+    let shares: Vec<f32> = value
+        .split(',')
+        .map(|s| s.trim().parse().map_err(|_| format!("invalid turn ratios: {}", value)))
+        .collect::<Result<_, _>>()?;
+    match shares[..] {
+        [straight, right, left] => Ok(TurnRatios { straight, right, left }),
+        _ => Err(format!("expected straight,right,left shares: {}", value)),
+    }
+}
+
+// `corridor [count]` or `grid [cols] [rows]`; anything else is the single intersection
+fn parse_network(args: &[String]) -> Result<Network, String> {
+    let count = |i: usize, default: usize| -> Result<usize, String> {
+        match args.get(i) {
+            Some(s) => s.parse().map_err(|_| format!("invalid count: {}", s)),
+            None => Ok(default),
+        }
+    };
+    match args.first().map(String::as_str) {
+        None | Some("single") => Ok(Network::single()),
+        Some("corridor") => Ok(Network::corridor(count(1, 3)?)),
+        Some("grid") => Ok(Network::grid(count(1, 3)?, count(2, 3)?)),
+        Some(other) => Err(format!("unknown layout: {}", other)),
+    }
+}
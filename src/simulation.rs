@@ -0,0 +1,552 @@
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
+
+use crate::conflict_monitor;
+use crate::connected;
+use crate::coordination::FixedTimePlan;
+use crate::driver::Driver;
+use crate::flash::FlashCause;
+use crate::intersection::{Controller, Intersection};
+use crate::metrics::Metrics;
+use crate::network::Network;
+use crate::pressure::{QueueControl, QueuePolicy};
+use crate::right_turn_on_red;
+use crate::roundabout;
+use crate::routing::{RouteChoice, Router};
+use crate::safety::{SafetyChecker, SafetyEvent};
+use crate::traffic_light::LightState;
+use crate::vehicle::{Direction, Turn, TurnRatios, Vehicle, VELOCITY};
+use crate::webster::Webster;
+
+pub const SAFE_DISTANCE: i32 = 50;
+pub const SINGLE_ROAD_PART: i32 = 350;
+pub const VEHICULE_LENGTH: i32 = 40;
+pub const FRAMES_PER_SECOND: i32 = 60;
+pub const STALL_SECONDS: i32 = 10;
+
+pub struct Simulation {
+    pub network: Network,
+    pub intersections: Vec<Intersection>,
+    pub vehicles: Vec<Vehicle>,
+    pub frame_count: i32,
+    pub metrics: Metrics,
+    // Random arrivals per boundary entry, in vehicles per minute
+    pub demand: f32,
+    pub turn_ratios: TurnRatios,
+    pub router: Router,
+    pub right_turn_on_red: bool,
+    // Gap, in pixels, kept to the vehicle ahead
+    pub safe_distance: i32,
+    // Frames during which every signal flashes for the night
+    pub night_flash: Option<Range<i32>>,
+    // Share of vehicles equipped to receive signal timing, from 0 to 1
+    pub penetration: f64,
+    // Whether each driver gets their own reaction time, speed and gap acceptance
+    pub varied_drivers: bool,
+    // Re-times the fixed-time plans to the flows they see at the end of every cycle
+    pub webster: Option<Webster>,
+    // Id of the next vehicle to enter, so runs with the same seed number their vehicles alike
+    next_id: u32,
+    last_spawn: HashMap<(Direction, usize), i32>,
+    pending: HashMap<(Direction, usize), VecDeque<Arrival>>,
+    rng: StdRng,
+    // Kept apart from `rng` so the penetration rate leaves the arrivals unchanged
+    equipment_rng: StdRng,
+    driver_rng: StdRng,
+    safety_checker: SafetyChecker,
+}
+
+// A vehicle waiting to enter the world, with its turns drawn when it arrived
+#[derive(Clone)]
+struct Arrival {
+    direction: Direction,
+    node: usize,
+    turns: VecDeque<Turn>,
+    destination: Option<usize>,
+    equipped: bool,
+    driver: Driver,
+}
+
+impl Simulation {
+    pub fn new(network: Network, seed: u64) -> Self {
+        let intersections = network
+            .nodes
+            .iter()
+            .map(|node| Intersection::new(node.id, node.center))
+            .collect();
+        let router = Router::new(&network, RouteChoice::TurnRatios, VELOCITY);
+        Simulation {
+            network,
+            intersections,
+            vehicles: Vec::new(),
+            frame_count: 0,
+            metrics: Metrics::default(),
+            demand: 0.,
+            turn_ratios: TurnRatios::default(),
+            router,
+            right_turn_on_red: false,
+            safe_distance: SAFE_DISTANCE,
+            night_flash: None,
+            penetration: 0.,
+            varied_drivers: false,
+            webster: None,
+            next_id: 1,
+            last_spawn: HashMap::new(),
+            pending: HashMap::new(),
+            rng: StdRng::seed_from_u64(seed),
+            equipment_rng: StdRng::seed_from_u64(seed.wrapping_add(1)),
+            driver_rng: StdRng::seed_from_u64(seed.wrapping_add(2)),
+            safety_checker: SafetyChecker::new(STALL_SECONDS * FRAMES_PER_SECOND),
+        }
+    }
+
+    pub fn set_controller(&mut self, controller: impl Fn() -> Controller) {
+        for intersection in &mut self.intersections {
+            intersection.controller = controller();
+        }
+    }
+
+    /// Puts every intersection under queue-driven control with `policy`.
+    pub fn set_queue_control(&mut self, policy: QueuePolicy) {
+        for intersection in &mut self.intersections {
+            let queue = QueueControl::new(policy, &self.network, intersection.node);
+            intersection.controller = Controller::Queue(queue);
+        }
+    }
+
+    /// Replaces every intersection's controller with the matching fixed-time plan.
+    pub fn set_plans(&mut self, plans: Vec<FixedTimePlan>) {
+        for (intersection, plan) in self.intersections.iter_mut().zip(plans) {
+            intersection.controller = Controller::FixedTime(plan);
+        }
+    }
+
+    /// Spawns a vehicle heading `direction` at a random boundary entry, if
+    /// there is room for it. Returns whether a vehicle was added.
+    pub fn spawn(&mut self, direction: Direction) -> bool {
+        let entries = self.network.entry_nodes(direction);
+        let Some(&node) = entries.choose(&mut self.rng) else {
+            return false;
+        };
+        self.spawn_at(direction, node)
+    }
+
+    pub fn spawn_at(&mut self, direction: Direction, node: usize) -> bool {
+        let arrival = self.new_arrival(direction, node);
+        self.try_spawn(&arrival)
+    }
+
+    // Draws everything random about a vehicle up front, so a given seed gives
+    // the same demand whatever happens to it once it is in the world
+    fn new_arrival(&mut self, direction: Direction, node: usize) -> Arrival {
+        let route = match (self.router.choice, self.network.entry_link(node, direction)) {
+            (RouteChoice::TurnRatios, _) | (_, None) => None,
+            (_, Some(origin)) => self.pick_route(origin),
+        };
+        let (turns, destination) = match route {
+            Some((turns, destination)) => (turns, Some(destination)),
+            None => {
+                let turns = (0..=self.network.nodes.len())
+                    .map(|_| self.turn_ratios.sample(&mut self.rng))
+                    .collect();
+                (turns, None)
+            }
+        };
+        let equipped = self.equipment_rng.random_bool(self.penetration.clamp(0., 1.));
+        let driver = if self.varied_drivers {
+            Driver::sample(&mut self.driver_rng)
+        } else {
+            Driver::default()
+        };
+        Arrival {
+            direction,
+            node,
+            turns,
+            destination,
+            equipped,
+            driver,
+        }
+    }
+
+    fn try_spawn(&mut self, arrival: &Arrival) -> bool {
+        let (direction, node) = (arrival.direction, arrival.node);
+        let center = self.network.nodes[node].center;
+        let edge = self.network.entry_edge(direction);
+
+        let mut turns = arrival.turns.clone();
+        let turn = turns.pop_front().expect("routes cross at least one intersection");
+        let mut vehicle = Vehicle::new(self.next_id, direction, node, center, edge, turn);
+        if !self.is_safe_to_spawn(&vehicle) {
+            return false;
+        }
+        self.next_id += 1;
+        vehicle.route = turns;
+        vehicle.destination = arrival.destination;
+        vehicle.equipped = arrival.equipped;
+        vehicle.driver = arrival.driver;
+        vehicle.snaps_to_lane = self.network.nodes.len() > 1;
+        vehicle.link = self.network.entry_link(node, direction);
+        vehicle.link_entered_at = self.frame_count;
+        vehicle.spawned_at = self.frame_count;
+        self.vehicles.push(vehicle);
+        self.last_spawn.insert((direction, node), self.frame_count);
+        true
+    }
+
+    // Random reachable destination for a vehicle entering on `origin`, and the route to it
+    fn pick_route(&mut self, origin: usize) -> Option<(VecDeque<Turn>, usize)> {
+        let mut exits = self.network.exit_links();
+        exits.shuffle(&mut self.rng);
+        exits.into_iter().find_map(|destination| {
+            self.router
+                .route(&self.network, origin, destination)
+                .filter(|turns| !turns.is_empty())
+                .map(|turns| (turns, destination))
+        })
+    }
+
+    /// Advances the whole world by one frame and returns the safety events
+    /// raised on it.
+    #[allow(clippy::needless_range_loop)]
+    pub fn step(&mut self) -> Vec<SafetyEvent> {
+        self.frame_count += 1;
+        self.generate_arrivals();
+
+        if let Some(webster) = &mut self.webster {
+            webster.observe(&self.vehicles, self.frame_count);
+        }
+
+        let mut faults = Vec::new();
+        for intersection in &mut self.intersections {
+            match &self.night_flash {
+                Some(night) if night.contains(&self.frame_count) => intersection.start_flash(FlashCause::Night),
+                Some(_) => intersection.end_flash(),
+                None => {}
+            }
+            if let (Some(webster), Controller::FixedTime(plan)) = (&self.webster, &mut intersection.controller) {
+                // Only between cycles, so no green is cut short
+                if (self.frame_count - plan.offset).rem_euclid(plan.cycle) == 0 {
+                    *plan = webster.plan_for(intersection.node, self.frame_count, self.safe_distance);
+                }
+            }
+            intersection.update(&self.vehicles, self.frame_count, self.safe_distance);
+            faults.extend(conflict_monitor::check(intersection, self.frame_count));
+        }
+
+        // Equipped vehicles within range hear their light's timing and adjust speed
+        for vehicle in self.vehicles.iter_mut().filter(|v| v.equipped) {
+            let spat = connected::broadcast(&self.intersections[vehicle.node], vehicle.direction);
+            vehicle.advised_speed = connected::advise(vehicle, &spat);
+        }
+
+        // A queued driver only starts reacting once the vehicle ahead has pulled
+        // away, so start-up delays add up back through the queue
+        let held: Vec<usize> = (0..self.vehicles.len())
+            .filter(|&i| {
+                let vehicle = &self.vehicles[i];
+                vehicle.at_standstill()
+                    && gap_ahead(vehicle, self.vehicles.iter().filter(|other| other.id != vehicle.id))
+                        .is_some_and(|gap| gap < self.safe_distance + VELOCITY)
+            })
+            .collect();
+        for i in held {
+            self.vehicles[i].hold();
+        }
+
+        // Right turners allowed through their red on this frame
+        let turning_on_red: HashSet<u32> = self
+            .vehicles
+            .iter()
+            .filter(|v| self.right_turn_on_red && !self.intersections[v.node].is_unsignalized())
+            .filter(|v| right_turn_on_red::may_turn(v, &self.vehicles))
+            .map(|v| v.id)
+            .collect();
+
+        // Compute tentative positions (with traffic light checks)
+        let mut tentatives: Vec<Vehicle> = self
+            .vehicles
+            .iter()
+            .map(|v| {
+                let mut tentative_v = v.clone();
+                let intersection = &self.intersections[v.node];
+                match intersection.controller {
+                    Controller::Roundabout => {
+                        let must_yield = roundabout::must_yield(v, &self.vehicles);
+                        tentative_v.update_roundabout(must_yield);
+                    }
+                    _ => {
+                        let mut signal = intersection.signal_for(v);
+                        if turning_on_red.contains(&v.id) {
+                            signal = LightState::Green;
+                        }
+                        tentative_v.update(signal)
+                    }
+                }
+                tentative_v
+            })
+            .collect();
+
+        let mut safe_to_move = vec![true; tentatives.len()];
+        for i in 0..tentatives.len() {
+            let (before, rest) = tentatives.split_at_mut(i);
+            let (current, after) = rest.split_first_mut().unwrap();
+            if let Some(distance) = gap_ahead(current, before.iter().chain(after.iter())) {
+                if distance < self.safe_distance {
+                    safe_to_move[i] = false;
+                }
+            }
+        }
+
+        // Keep a gap to the vehicle ahead on a roundabout, and behind when joining it
+        for (i, safe) in safe_to_move.iter_mut().enumerate() {
+            let Some(angle) = tentatives[i].ring_angle else {
+                continue;
+            };
+            let joining = self.vehicles[i].ring_angle.is_none();
+            let node = self.vehicles[i].node;
+            if self.vehicles.iter().enumerate().any(|(j, other)| {
+                j != i
+                    && other.node == node
+                    && other.ring_angle.is_some_and(|other_angle| {
+                        roundabout::arc(angle, other_angle) < roundabout::MIN_GAP
+                            || (joining && roundabout::arc(other_angle, angle) < roundabout::MIN_GAP)
+                    })
+            }) {
+                *safe = false;
+            }
+        }
+
+        // Don't block the box: only enter when the exit lane has room. Going
+        // straight on through a green behind traffic still moving off, there
+        // will be room by the time it is needed, provided nothing is stopped
+        // within a space of the lane per vehicle ahead, itself included.
+        let space = VEHICULE_LENGTH + self.safe_distance;
+        for (i, safe) in safe_to_move.iter_mut().enumerate() {
+            if self.vehicles[i].in_intersection || !tentatives[i].in_intersection {
+                continue;
+            }
+            let straight = tentatives[i].turn == Turn::Straight
+                && !self.intersections[tentatives[i].node].is_unsignalized();
+            let mut length = space;
+            loop {
+                let exit = tentatives[i].exit_area(length);
+                let ahead: Vec<&Vehicle> = self
+                    .vehicles
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, other)| j != i && other.rect.has_intersection(exit))
+                    .map(|(_, other)| other)
+                    .collect();
+                if ahead.iter().any(|other| !straight || other.stopped_frames > 0) {
+                    *safe = false;
+                    break;
+                }
+                let needed = (ahead.len() as i32 + 1) * space;
+                if needed <= length {
+                    break;
+                }
+                length = needed;
+            }
+        }
+
+        let mut collisions = vec![false; tentatives.len()];
+        for i in 0..tentatives.len() {
+            for j in (i + 1)..tentatives.len() {
+                let a = &tentatives[i];
+                let b = &tentatives[j];
+
+                if (a.in_intersection || b.in_intersection) && !a.has_turned && !b.has_turned {
+                    continue;
+                }
+
+                // A vehicle held back already stays where it is
+                let (current_a, current_b) = (self.vehicles[i].rect, self.vehicles[j].rect);
+                let next_a = if safe_to_move[i] { a.rect } else { current_a };
+                let next_b = if safe_to_move[j] { b.rect } else { current_b };
+                if !next_a.has_intersection(next_b) {
+                    continue;
+                }
+                let a_first = if current_a.has_intersection(current_b) {
+                    // Already overlapping: only the leading vehicle, free to
+                    // move and whose move takes it furthest out of the
+                    // other, may go
+                    let before = overlap(current_a, current_b);
+                    let gain = |k: usize, moved: i64| if safe_to_move[k] { before - moved } else { i64::MIN };
+                    let (gain_a, gain_b) = (gain(i, overlap(a.rect, current_b)), gain(j, overlap(current_a, b.rect)));
+                    gain_a > gain_b || (gain_a == gain_b && a.id < b.id)
+                } else if safe_to_move[i] != safe_to_move[j] {
+                    // Otherwise a vehicle moving into one held back waits
+                    !safe_to_move[i]
+                } else {
+                    // and the vehicle that has been in the world longer goes first
+                    a.id < b.id
+                };
+                if a_first {
+                    collisions[j] = true;
+                } else {
+                    collisions[i] = true;
+                }
+            }
+        }
+
+        // Update original vehicles only if safe
+        for (i, vehicle) in self.vehicles.iter_mut().enumerate() {
+            let moved = safe_to_move[i] && !collisions[i] && tentatives[i].rect != vehicle.rect;
+            if safe_to_move[i] && !collisions[i] {
+                *vehicle = tentatives[i].clone();
+            }
+            vehicle.track_motion(moved);
+            let travelled = if moved { vehicle.speed } else { 0. };
+            let (fuel, co2) = (vehicle.emissions.fuel, vehicle.emissions.co2);
+            vehicle.emissions.record(travelled as f64);
+            self.metrics.fuel += vehicle.emissions.fuel - fuel;
+            self.metrics.co2 += vehicle.emissions.co2 - co2;
+        }
+
+        // Hand vehicles over to the next intersection along their heading
+        for vehicle in &mut self.vehicles {
+            if vehicle.has_cleared_intersection() {
+                if let Some(next) = self.network.next_node(vehicle.node, vehicle.direction) {
+                    if let Some(link) = vehicle.link {
+                        self.router.record(link, self.frame_count - vehicle.link_entered_at);
+                    }
+                    vehicle.link = Some(self.network.outgoing_link(vehicle.node, vehicle.direction));
+                    vehicle.link_entered_at = self.frame_count;
+
+                    let turn = match vehicle.route.pop_front() {
+                        Some(turn) => turn,
+                        None => self.turn_ratios.sample(&mut self.rng),
+                    };
+                    vehicle.enter_node(next, self.network.nodes[next].center, turn);
+                }
+            }
+        }
+
+        let (width, height) = (self.network.width, self.network.height);
+        let (metrics, frame) = (&mut self.metrics, self.frame_count);
+        self.vehicles.retain(|v| {
+            let on_screen = is_on_screen(v, width, height);
+            if !on_screen {
+                metrics.record(v, frame);
+            }
+            on_screen
+        });
+
+        let intersections = &self.intersections;
+        let mut events = self.safety_checker.check(
+            &self.vehicles,
+            |v| match intersections[v.node].light_for(v.direction) {
+                // Only the right turners that were clear to go on red
+                LightState::Red if turning_on_red.contains(&v.id) => LightState::Green,
+                state => state,
+            },
+            self.frame_count,
+        );
+        events.extend(faults);
+        events
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+        self.network.draw(canvas);
+        for intersection in &self.intersections {
+            intersection.draw(canvas);
+        }
+        for vehicle in &self.vehicles {
+            vehicle.draw(canvas);
+        }
+        self.safety_checker.draw(canvas, &self.vehicles);
+    }
+
+    // Random arrivals at every boundary entry; vehicles that can't enter yet
+    // wait in line off screen
+    fn generate_arrivals(&mut self) {
+        let probability = (self.demand / (60 * FRAMES_PER_SECOND) as f32) as f64;
+        for direction in [Direction::North, Direction::South, Direction::East, Direction::West] {
+            for node in self.network.entry_nodes(direction) {
+                if probability > 0. && self.rng.random_bool(probability.min(1.)) {
+                    let arrival = self.new_arrival(direction, node);
+                    self.pending.entry((direction, node)).or_default().push_back(arrival);
+                }
+                let Some(arrival) = self.pending.get(&(direction, node)).and_then(|q| q.front()) else {
+                    continue;
+                };
+                let arrival = arrival.clone();
+                if self.try_spawn(&arrival) {
+                    self.pending.get_mut(&(direction, node)).map(VecDeque::pop_front);
+                }
+            }
+        }
+    }
+
+    fn is_safe_to_spawn(&self, candidate: &Vehicle) -> bool {
+        let direction = candidate.direction;
+        if let Some(last_frame) = self.last_spawn.get(&(direction, candidate.node)) {
+            if self.frame_count - last_frame < self.safe_distance / 2 {
+                return false;
+            }
+        }
+
+        let edge = self.network.entry_edge(direction);
+        let (spawn_coord, is_vertical) = match direction {
+            Direction::North => (edge - 40, true),
+            Direction::South => (edge + 80, true),
+            Direction::East => (edge + 80, false),
+            Direction::West => (edge - 40, false),
+        };
+
+        // Check distance from existing vehicles in the same lane
+        for vehicle in self.vehicles.iter().filter(|v| v.shares_lane(candidate)) {
+            let vehicle_pos = if is_vertical {
+                vehicle.rect.y() + vehicle.rect.height() as i32
+            } else {
+                vehicle.rect.x() + vehicle.rect.width() as i32
+            };
+
+            let distance = (vehicle_pos - spawn_coord).abs();
+            if distance < self.safe_distance {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+// Function to check if a vehicle is still on screen
+fn is_on_screen(vehicle: &Vehicle, width: i32, height: i32) -> bool {
+    let rect = vehicle.rect;
+    match vehicle.direction {
+        Direction::North => rect.y() > -50,
+        Direction::South => rect.y() < height + 50,
+        Direction::East => rect.x() < width + 50,
+        Direction::West => rect.x() > -50,
+    }
+}
+
+// Area two footprints share
+fn overlap(a: Rect, b: Rect) -> i64 {
+    a.intersection(b).map_or(0, |r| r.width() as i64 * r.height() as i64)
+}
+
+// Distance from the front of `vehicle` to the nearest of `others` ahead of it in its lane
+fn gap_ahead<'a>(vehicle: &Vehicle, others: impl Iterator<Item = &'a Vehicle>) -> Option<i32> {
+    // Turning off inside the box, it never reaches traffic already beyond it
+    let turning_off = vehicle.in_intersection && !vehicle.has_turned && vehicle.turn != Turn::Straight;
+    others
+        .filter(|other| vehicle.shares_lane(other))
+        .filter(|other| !turning_off || other.node == vehicle.node)
+        .map(|other| match vehicle.direction {
+            Direction::North => vehicle.rect.y() - (other.rect.y() + other.rect.height() as i32),
+            Direction::South => other.rect.y() - (vehicle.rect.y() + vehicle.rect.height() as i32),
+            Direction::East => other.rect.x() - (vehicle.rect.x() + vehicle.rect.width() as i32),
+            Direction::West => vehicle.rect.x() - (other.rect.x() + other.rect.width() as i32),
+        })
+        .filter(|&distance| distance >= 0)
+        .min()
+}
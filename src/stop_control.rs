@@ -0,0 +1,128 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::driver::NOMINAL_GAP_FRAMES;
+use crate::traffic_light::LightState;
+use crate::vehicle::{Direction, Vehicle, FULL_STOP_FRAMES, VELOCITY};
+
+/// Unsignalized stop control. All-way: every approach stops and vehicles go
+/// first come, first served. Two-way: the East-West road is major and never
+/// stops; North-South vehicles stop and wait for a gap in it.
+pub struct StopControl {
+    pub two_way: bool,
+    // Vehicles waiting at a stop line, in order of arrival, with the frame they got there
+    arrivals: Vec<(u32, i32)>,
+    released: Option<u32>,
+}
+
+impl StopControl {
+    pub fn all_way() -> Self {
+        StopControl {
+            two_way: false,
+            arrivals: Vec::new(),
+            released: None,
+        }
+    }
+
+    pub fn two_way() -> Self {
+        StopControl {
+            two_way: true,
+            ..StopControl::all_way()
+        }
+    }
+
+    pub fn is_major(&self, direction: Direction) -> bool {
+        self.two_way && matches!(direction, Direction::East | Direction::West)
+    }
+
+    /// Records arrivals at the stop lines of intersection `node` and picks the
+    /// one vehicle, if any, allowed into the box on this frame.
+    pub fn update(&mut self, node: usize, center: (i32, i32), vehicles: &[Vehicle], frame: i32) {
+        let waiting: Vec<&Vehicle> = vehicles
+            .iter()
+            .filter(|v| v.node == node && !v.in_intersection && !v.has_turned && v.is_at_stop_line())
+            .filter(|v| !self.is_major(v.direction))
+            .collect();
+        self.arrivals.retain(|(id, _)| waiting.iter().any(|v| v.id == *id));
+        for v in waiting {
+            if !self.arrivals.iter().any(|(id, _)| *id == v.id) {
+                self.arrivals.push((v.id, frame));
+            }
+        }
+
+        self.released = None;
+        let (cx, cy) = center;
+        let square = Rect::new(cx - 50, cy - 50, 100, 100);
+        let occupied = vehicles
+            .iter()
+            .any(|v| v.node == node && v.in_intersection && v.rect.has_intersection(square));
+        if occupied {
+            return;
+        }
+        let Some(&(id, since)) = self.arrivals.first() else {
+            return;
+        };
+        if frame - since < FULL_STOP_FRAMES {
+            return;
+        }
+        // Minor approaches only go if no major-road vehicle reaches the box
+        // within the waiting driver's acceptable gap
+        let gap = vehicles
+            .iter()
+            .find(|v| v.id == id)
+            .map_or(NOMINAL_GAP_FRAMES, |v| v.driver.acceptable_gap);
+        if self.two_way && major_traffic_within_gap(node, center, vehicles, gap) {
+            return;
+        }
+        self.released = Some(id);
+    }
+
+    /// Green for vehicles that may enter: the released one and major-road traffic.
+    pub fn signal_for(&self, vehicle: &Vehicle) -> LightState {
+        if self.is_major(vehicle.direction) || self.released == Some(vehicle.id) {
+            LightState::Green
+        } else {
+            LightState::Red
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, center: (i32, i32)) {
+        let (cx, cy) = center;
+        for direction in [Direction::North, Direction::South, Direction::East, Direction::West] {
+            if self.is_major(direction) {
+                continue;
+            }
+            // Same corner as the traffic light for that approach
+            let (x, y) = match direction {
+                Direction::North => (cx + 60, cy + 60),
+                Direction::South => (cx - 80, cy - 80),
+                Direction::East => (cx - 80, cy + 60),
+                Direction::West => (cx + 60, cy - 80),
+            };
+            canvas.set_draw_color(Color::RGB(200, 20, 20));
+            let _ = canvas.fill_rect(Rect::new(x, y, 20, 20));
+            canvas.set_draw_color(Color::RGB(255, 255, 255));
+            let _ = canvas.fill_rect(Rect::new(x + 3, y + 8, 14, 4));
+        }
+    }
+}
+
+fn major_traffic_within_gap(node: usize, center: (i32, i32), vehicles: &[Vehicle], gap: i32) -> bool {
+    let cx = center.0;
+    vehicles
+        .iter()
+        .filter(|v| v.node == node && !v.in_intersection && !v.has_turned)
+        // A vehicle standing still, held up by traffic beyond the box, is not closing in
+        .filter(|v| !v.at_standstill())
+        .any(|v| {
+            let distance = match v.direction {
+                Direction::East => cx - 50 - (v.rect.x() + v.rect.width() as i32),
+                Direction::West => v.rect.x() - (cx + 50),
+                _ => return false,
+            };
+            // A vehicle at the edge of the box may be a few pixels past it before it counts as inside
+            distance < gap * VELOCITY
+        })
+}
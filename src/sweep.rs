@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::metrics::TripTotals;
+use crate::scenario;
+
+/// One combination of option values and what running it produced.
+pub struct Outcome {
+    pub options: BTreeMap<String, String>,
+    pub totals: TripTotals,
+    pub equipped: TripTotals,
+    pub unequipped: TripTotals,
+    pub safety_events: usize,
+}
+
+/// The values an option takes in a sweep: `;`-separated items, each a single
+/// value or an inclusive numeric range `start..end` with an optional `:step`.
+/// `5..15:5;30` is 5, 10, 15 and 30.
+pub fn expand(value: &str) -> Result<Vec<String>, String> {
+    let mut values = Vec::new();
+    for item in value.split(';') {
+        let Some((start, rest)) = item.split_once("..") else {
+            values.push(item.to_string());
+            continue;
+        };
+        let (end, step) = rest.split_once(':').unwrap_or((rest, "1"));
+        let number = |s: &str| -> Result<f64, String> {
+            s.parse().map_err(|_| format!("invalid range: {}", item))
+        };
+        let (start, end, step) = (number(start)?, number(end)?, number(step)?);
+        if step <= 0. {
+            return Err(format!("range step must be positive: {}", item));
+        }
+        let mut i = 0.;
+        while start + i * step <= end + 1e-9 {
+            // Rounded so 0.1 steps print as 0.3, not 0.30000000000000004
+            let x = ((start + i * step) * 1e6).round() / 1e6;
+            values.push(x.to_string());
+            i += 1.;
+        }
+    }
+    Ok(values)
+}
+
+/// Every combination of the values of `options`, each expanded with `expand`.
+pub fn combinations(options: &HashMap<String, String>) -> Result<Vec<BTreeMap<String, String>>, String> {
+    let mut combos = vec![BTreeMap::new()];
+    let sorted: BTreeMap<_, _> = options.iter().collect();
+    for (name, value) in sorted {
+        let values = expand(value)?;
+        combos = combos
+            .into_iter()
+            .flat_map(|combo| {
+                values.iter().map(move |v| {
+                    let mut combo = combo.clone();
+                    combo.insert(name.clone(), v.clone());
+                    combo
+                })
+            })
+            .collect();
+    }
+    Ok(combos)
+}
+
+/// Runs every combination on `layout` for `frames` frames, spread over
+/// `threads` worker threads. Outcomes come back in the order of `combos`.
+pub fn run(
+    layout: &[String],
+    combos: Vec<BTreeMap<String, String>>,
+    frames: i32,
+    threads: usize,
+) -> Result<Vec<Outcome>, String> {
+    // Surface option errors before spending any time simulating
+    let mut runs = Vec::new();
+    for combo in &combos {
+        let seed: u64 = match combo.get("seed") {
+            Some(seed) => seed.parse().map_err(|_| format!("invalid seed: {}", seed))?,
+            None => 0,
+        };
+        let options: HashMap<String, String> = combo.clone().into_iter().collect();
+        scenario::build(layout, &options, seed)?;
+        runs.push((options, seed));
+    }
+
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some((options, seed)) = runs.get(i) else {
+                    break;
+                };
+                let mut sim = scenario::build(layout, options, *seed).expect("options were validated");
+                let mut safety_events = 0;
+                for _ in 0..frames {
+                    safety_events += sim.step().len();
+                }
+                let totals = [sim.metrics.total, sim.metrics.equipped, sim.metrics.unequipped];
+                outcomes.lock().unwrap().push((i, totals, safety_events));
+            });
+        }
+    });
+
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|(i, _, _)| *i);
+    Ok(outcomes
+        .into_iter()
+        .zip(combos)
+        .map(|((_, [totals, equipped, unequipped], safety_events), options)| Outcome {
+            options,
+            totals,
+            equipped,
+            unequipped,
+            safety_events,
+        })
+        .collect())
+}
@@ -3,16 +3,24 @@ use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 
+use crate::font;
+use crate::simulation::FRAMES_PER_SECOND;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LightState {
     Red,
     Green,
+    // Stop, then go when the way is clear, as at a stop sign
+    FlashingRed,
+    // Proceed with caution
+    FlashingYellow,
 }
 
 pub struct TrafficLight {
     rect: Rect,
     pub state: LightState,
+    // Frames left in `state` and the state after it, when the controller knows them
+    schedule: Option<(i32, LightState)>,
 }
 
 impl TrafficLight {
@@ -20,8 +28,12 @@ impl TrafficLight {
         TrafficLight {
             rect: Rect::new(x, y, w, h),
             state,
+            schedule: None,
         }
     }
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.rect.contains_point((x, y))
+    }
     pub fn update(&mut self, is_green: bool) {
         self.state = if is_green {
             LightState::Green
@@ -29,6 +41,20 @@ impl TrafficLight {
             LightState::Red
         };
     }
+    /// Frames, from the current one, the light stays in its state, when known.
+    pub fn remaining(&self) -> Option<i32> {
+        self.schedule.map(|(frames, _)| frames)
+    }
+
+    /// The state the light changes to next, when known.
+    pub fn next_state(&self) -> Option<LightState> {
+        self.schedule.map(|(_, next)| next)
+    }
+
+    pub fn set_schedule(&mut self, schedule: Option<(i32, LightState)>) {
+        self.schedule = schedule;
+    }
+
     pub fn draw(&self, canvas: &mut Canvas<Window>) {
         canvas.set_draw_color(Color::RGB(20, 20, 20));
         let housing = Rect::new(
@@ -42,6 +68,8 @@ impl TrafficLight {
         let color = match self.state {
             LightState::Red => Color::RGB(220, 20, 20),
             LightState::Green => Color::RGB(20, 180, 20),
+            LightState::FlashingRed => Color::RGB(220, 20, 20),
+            LightState::FlashingYellow => Color::RGB(230, 190, 20),
         };
 
         canvas.set_draw_color(color);
@@ -56,4 +84,14 @@ impl TrafficLight {
         );
         let _ = canvas.fill_rect(highlight);
     }
+
+    /// Seconds left in the current state, beside the light, when known.
+    pub fn draw_countdown(&self, canvas: &mut Canvas<Window>) {
+        let Some(frames) = self.remaining() else {
+            return;
+        };
+        let seconds = (frames + FRAMES_PER_SECOND - 1) / FRAMES_PER_SECOND;
+        canvas.set_draw_color(Color::RGB(255, 255, 255));
+        font::draw_text(canvas, self.rect.x(), self.rect.bottom() + 6, 2, &seconds.to_string());
+    }
 }
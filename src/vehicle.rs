@@ -3,9 +3,28 @@ use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
+use std::collections::VecDeque;
 
+use crate::driver::Driver;
+use crate::emissions::Emissions;
+use crate::roundabout;
+use crate::simulation::FRAMES_PER_SECOND;
 use crate::traffic_light::LightState;
 
+pub const VELOCITY: i32 = 5;
+// Speed gained per frame when pulling away, reaching VELOCITY within half a second
+pub const ACCELERATION: f32 = VELOCITY as f32 / (FRAMES_PER_SECOND / 2) as f32;
+// Slowest a moving vehicle goes: it covers at least a pixel every frame
+pub const CREEP_SPEED: f32 = 1.;
+// Held up for less than this, a vehicle has only slowed down; longer, it has
+// come to a standstill and must react and pull away again
+const STANDSTILL_FRAMES: i32 = 10;
+// Frames a vehicle stands still at a stop line to have made a full stop: at
+// a stop sign, a flashing red, or before turning right on red
+pub const FULL_STOP_FRAMES: i32 = FRAMES_PER_SECOND / 2;
+// Within this distance of a flashing yellow, vehicles slow to half speed
+const CAUTION_DISTANCE: i32 = 100;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
     North,
@@ -19,90 +38,360 @@ pub enum Turn {
     Right,
     Left,
 }
+/// Share of vehicles going straight, right and left at each intersection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TurnRatios {
+    pub straight: f32,
+    pub right: f32,
+    pub left: f32,
+}
+
+impl Default for TurnRatios {
+    fn default() -> Self {
+        TurnRatios {
+            straight: 1.,
+            right: 1.,
+            left: 1.,
+        }
+    }
+}
+
+impl TurnRatios {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Turn {
+        let total = self.straight + self.right + self.left;
+        let r = rng.random_range(0.0..total.max(f32::EPSILON));
+        if r < self.straight {
+            Turn::Straight
+        } else if r < self.straight + self.right {
+            Turn::Right
+        } else {
+            Turn::Left
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Vehicle {
+    pub id: u32,
     pub rect: Rect,
     pub direction: Direction,
     velocity: i32,
+    // Pixels per frame before rounding to whole-pixel steps
+    pub speed: f32,
+    // Fraction of a pixel travelled but not yet moved, at speeds below VELOCITY
+    carry: f32,
     color: Color,
-    turn: Turn,
+    pub turn: Turn,
     pub has_turned: bool,
     pub in_intersection: bool,
+    pub node: usize,
+    center: (i32, i32),
+    pub origin: Direction,
+    pub stops: u32,
+    stopped: bool,
+    // Consecutive frames spent standing still, and all of them since spawning
+    pub stopped_frames: i32,
+    pub delay_frames: i32,
+    // Turns still to make at the intersections after the current one
+    pub route: VecDeque<Turn>,
+    pub destination: Option<usize>,
+    // Network link currently travelled and the frame it was entered on
+    pub link: Option<usize>,
+    pub link_entered_at: i32,
+    pub spawned_at: i32,
+    // Position on a roundabout's circulating lane, while on it
+    pub ring_angle: Option<f32>,
+    // Receives signal timing and follows speed advice, see `connected`
+    pub equipped: bool,
+    pub advised_speed: Option<f32>,
+    pub emissions: Emissions,
+    pub driver: Driver,
+    // Consecutive frames the driver has been free to move off from standstill, and their signal has been red
+    ready_frames: i32,
+    red_frames: i32,
+    // Turns end centred on the new lane, for turn points at later
+    // intersections to line up; a single intersection has none
+    pub snaps_to_lane: bool,
 }
 
 impl Vehicle {
-    pub fn new(direction: Direction) -> Self {
-        let velocity = 5;
+    /// Spawns vehicle `id` heading `direction` towards intersection `node`
+    /// (centred on `center`), starting on the world boundary at `edge`.
+    pub fn new(id: u32, direction: Direction, node: usize, center: (i32, i32), edge: i32, turn: Turn) -> Self {
+        let velocity = VELOCITY;
+        let (cx, cy) = center;
         let rect = match direction {
-            Direction::North => Rect::new(415, 800, 20, 40),
-            Direction::South => Rect::new(365, -40, 20, 40),
-            Direction::East => Rect::new(-40, 415, 40, 20),
-            Direction::West => Rect::new(800, 365, 40, 20),
-        };
-        let mut rng = rand::rng();
-        let (turn, color) = match rng.random_range(0..3) {
-            0 => (Turn::Straight, Color::RGB(180, 180, 200)),  // Silver/gray for straight
-            1 => (Turn::Right, Color::RGB(200, 60, 60)),       // Deep red for right turn
-            2 => (Turn::Left, Color::RGB(60, 100, 180)),       // Deep blue for left turn
-            _ => unreachable!(),
+            Direction::North => Rect::new(cx + 15, edge, 20, 40),
+            Direction::South => Rect::new(cx - 35, edge - 40, 20, 40),
+            Direction::East => Rect::new(edge - 40, cy + 15, 40, 20),
+            Direction::West => Rect::new(edge, cy - 35, 40, 20),
         };
+        let color = turn_color(turn);
         Vehicle {
+            id,
             rect,
             direction,
             velocity,
+            speed: velocity as f32,
+            carry: 0.,
             color,
             turn,
             has_turned: false,
             in_intersection: false,
+            node,
+            center,
+            origin: direction,
+            stops: 0,
+            stopped: false,
+            stopped_frames: 0,
+            delay_frames: 0,
+            route: VecDeque::new(),
+            destination: None,
+            link: None,
+            link_entered_at: 0,
+            spawned_at: 0,
+            ring_angle: None,
+            equipped: false,
+            advised_speed: None,
+            emissions: Emissions::default(),
+            driver: Driver::default(),
+            ready_frames: 0,
+            red_frames: 0,
+            snaps_to_lane: false,
+        }
+    }
+
+    /// Counts a stop each time the vehicle goes from moving to standing still,
+    /// and the frames it spends standing.
+    pub fn track_motion(&mut self, moved: bool) {
+        if !moved && !self.stopped {
+            self.stops += 1;
+        }
+        self.stopped = !moved;
+        self.stopped_frames = if moved { 0 } else { self.stopped_frames + 1 };
+        if moved {
+            self.ready_frames = 0;
+        }
+        if !moved {
+            self.delay_frames += 1;
+        }
+    }
+
+    pub fn at_standstill(&self) -> bool {
+        self.stopped_frames >= STANDSTILL_FRAMES
+    }
+
+    /// Restarts the driver's reaction from standstill: the vehicle ahead has
+    /// not pulled away yet, so there is nothing to react to.
+    pub fn hold(&mut self) {
+        self.ready_frames = 0;
+    }
+
+    /// Hands the vehicle over to the next intersection on its path.
+    pub fn enter_node(&mut self, node: usize, center: (i32, i32), turn: Turn) {
+        self.node = node;
+        self.center = center;
+        self.turn = turn;
+        self.color = turn_color(turn);
+        self.has_turned = false;
+        self.in_intersection = false;
+    }
+
+    /// True once a vehicle that entered its intersection is fully out of the box.
+    pub fn has_cleared_intersection(&self) -> bool {
+        if !self.in_intersection {
+            return false;
+        }
+        let (cx, cy) = self.center;
+        match self.direction {
+            Direction::North => self.rect.y() + (self.rect.height() as i32) < cy - 50,
+            Direction::South => self.rect.y() > cy + 50,
+            Direction::East => self.rect.x() > cx + 50,
+            Direction::West => self.rect.x() + (self.rect.width() as i32) < cx - 50,
+        }
+    }
+
+    /// Distance from the front of the vehicle to the edge of its intersection
+    /// box; negative once it is past that edge.
+    pub fn distance_to_box(&self) -> i32 {
+        let (cx, cy) = self.center;
+        match self.direction {
+            Direction::North => self.rect.y() - (cy + 50),
+            Direction::South => cy - 50 - (self.rect.y() + self.rect.height() as i32),
+            Direction::East => cx - 50 - (self.rect.x() + self.rect.width() as i32),
+            Direction::West => self.rect.x() - (cx + 50),
+        }
+    }
+
+    /// Heading once the vehicle has made its turn at the current intersection.
+    pub fn exit_direction(&self) -> Direction {
+        turned_direction(self.direction, self.turn)
+    }
+
+    /// Stretch of the exit lane just past the box that must be free before
+    /// entering, so queues spilling back never block the intersection.
+    pub fn exit_area(&self, length: i32) -> Rect {
+        let (cx, cy) = self.center;
+        let length = length as u32;
+        match self.exit_direction() {
+            Direction::North => Rect::new(cx + 15, cy - 50 - length as i32, 20, length),
+            Direction::South => Rect::new(cx - 35, cy + 50, 20, length),
+            Direction::East => Rect::new(cx + 50, cy + 15, length, 20),
+            Direction::West => Rect::new(cx - 50 - length as i32, cy - 35, length, 20),
+        }
+    }
+
+    /// Whether `other` occupies the same lane (overlapping lateral span).
+    pub fn shares_lane(&self, other: &Vehicle) -> bool {
+        if self.direction != other.direction || self.ring_angle.is_some() || other.ring_angle.is_some() {
+            return false;
+        }
+        match self.direction {
+            Direction::North | Direction::South => {
+                self.rect.x() < other.rect.right() && other.rect.x() < self.rect.right()
+            }
+            Direction::East | Direction::West => {
+                self.rect.y() < other.rect.bottom() && other.rect.y() < self.rect.bottom()
+            }
         }
     }
 
     pub fn update(&mut self, light_state: LightState) {
+        self.red_frames = if light_state == LightState::Red { self.red_frames + 1 } else { 0 };
         if !self.in_intersection && !self.has_turned && self.should_stop_at_light(light_state) {
+            self.ready_frames = 0;
             return;
         }
-        if !self.has_turned && !self.in_intersection {
-            // Detect entry into intersection bounds (e.g., 350 <= x/y <= 450)
-            let in_intersection = match self.direction {
-                Direction::North => self.rect.y() <= 450,
-                Direction::South => self.rect.y() + self.rect.height() as i32 >= 350,
-                Direction::East => self.rect.x() + self.rect.width() as i32 >= 350,
-                Direction::West => self.rect.x() <= 450,
-            };
-
-            if in_intersection {
-                self.in_intersection = true;
-            }
+        // From standstill, the driver takes their reaction time before moving off
+        if self.at_standstill() && self.ready_frames < self.driver.reaction_frames {
+            self.ready_frames += 1;
+            return;
         }
+        // From CAUTION_DISTANCE before the box until the front is through its 100 pixels
+        let cautious = light_state == LightState::FlashingYellow
+            && !self.has_turned
+            && (-100..CAUTION_DISTANCE).contains(&self.distance_to_box());
+        let mut speed = self.advised_speed.unwrap_or(VELOCITY as f32).min(self.driver.desired_speed);
+        if cautious {
+            speed = speed.min(VELOCITY as f32 / 2.);
+        }
+        // Pulling away, speed builds up rather than jumping to the limit
+        let current = if self.at_standstill() { 0. } else { self.speed };
+        speed = speed.min(current + ACCELERATION).max(CREEP_SPEED);
+        self.pace(speed);
+        // Come to rest on the stop line rather than rolling over it
+        let distance = self.distance_to_box();
+        if matches!(light_state, LightState::Red | LightState::FlashingRed) && !self.has_turned && distance > 0 {
+            self.velocity = self.velocity.min(distance);
+        }
+        let (cx, cy) = self.center;
+        self.detect_entry();
         if !self.has_turned {
+            // Offsets from the intersection centre
             let turn_point = match (self.direction, self.turn) {
                 // Left turns
-                (Direction::West, Turn::Left) => (355, 375),
-                (Direction::North, Turn::Left) => (425, 355),
-                (Direction::South, Turn::Left) => (375, 445),
-                (Direction::East, Turn::Left) => (445, 425),
+                (Direction::West, Turn::Left) => (-45, -25),
+                (Direction::North, Turn::Left) => (25, -45),
+                (Direction::South, Turn::Left) => (-25, 45),
+                (Direction::East, Turn::Left) => (45, 25),
 
                 // Right turns
-                (Direction::West, Turn::Right) => (405, 375),
-                (Direction::North, Turn::Right) => (420, 405),
-                (Direction::South, Turn::Right) => (365, 395),
-                (Direction::East, Turn::Right) => (395, 415),
+                (Direction::West, Turn::Right) => (5, -25),
+                (Direction::North, Turn::Right) => (20, 5),
+                (Direction::South, Turn::Right) => (-35, -5),
+                (Direction::East, Turn::Right) => (-5, 15),
 
                 // Straight - use center of intersection
-                (Direction::West, Turn::Straight) => (400, 400),
-                (Direction::East, Turn::Straight) => (400, 400),
-                (Direction::North, Turn::Straight) => (400, 400),
-                (Direction::South, Turn::Straight) => (400, 400),
+                (_, Turn::Straight) => (0, 0),
             };
 
-            let point_rect = Rect::new(turn_point.0, turn_point.1, 1, 1);
+            let point_rect = Rect::new(cx + turn_point.0, cy + turn_point.1, 1, 1);
             if self.rect.has_intersection(point_rect) {
                 self.apply_turn();
                 self.has_turned = true;
             }
         }
 
-        // Continue movement
+        self.advance();
+    }
+
+    /// Movement through a roundabout: give way at the entry, circulate, then
+    /// peel off towards the exit for this vehicle's turn.
+    pub fn update_roundabout(&mut self, must_yield: bool) {
+        if let Some(angle) = self.ring_angle {
+            let exit = roundabout::exit_angle(self.exit_direction());
+            let step = self.velocity as f32 / roundabout::RADIUS;
+            if roundabout::arc(angle, exit) <= step {
+                self.leave_ring(exit);
+            } else {
+                self.place_on_ring(angle + step);
+            }
+            return;
+        }
+        if !self.in_intersection && !self.has_turned && must_yield && self.is_at_stop_line() {
+            return;
+        }
+        self.detect_entry();
+        if self.in_intersection && !self.has_turned {
+            let (ex, ey) = roundabout::entry_point(self.center, self.direction);
+            let center = self.rect.center();
+            let reached = match self.direction {
+                Direction::North => center.y - self.velocity <= ey,
+                Direction::South => center.y + self.velocity >= ey,
+                Direction::East => center.x + self.velocity >= ex,
+                Direction::West => center.x - self.velocity <= ex,
+            };
+            if reached {
+                self.place_on_ring(roundabout::entry_angle(self.direction));
+                return;
+            }
+        }
+        self.advance();
+    }
+
+    fn place_on_ring(&mut self, angle: f32) {
+        let angle = angle.rem_euclid(2. * std::f32::consts::PI);
+        let (x, y) = roundabout::point(self.center, angle);
+        self.rect = oriented_rect(roundabout::heading(angle), x, y);
+        self.ring_angle = Some(angle);
+    }
+
+    fn leave_ring(&mut self, exit: f32) {
+        let (x, y) = roundabout::point(self.center, exit);
+        self.direction = self.exit_direction();
+        self.rect = oriented_rect(self.direction, x, y);
+        self.ring_angle = None;
+        self.has_turned = true;
+    }
+
+    // Detect entry into intersection bounds (e.g., 350 <= x/y <= 450)
+    fn detect_entry(&mut self) {
+        if self.has_turned || self.in_intersection {
+            return;
+        }
+        let (cx, cy) = self.center;
+        let in_intersection = match self.direction {
+            Direction::North => self.rect.y() <= cy + 50,
+            Direction::South => self.rect.y() + self.rect.height() as i32 >= cy - 50,
+            Direction::East => self.rect.x() + self.rect.width() as i32 >= cx - 50,
+            Direction::West => self.rect.x() <= cx + 50,
+        };
+
+        if in_intersection {
+            self.in_intersection = true;
+        }
+    }
+
+    // Continue movement
+    // Sets this frame's whole-pixel step for `speed`, carrying the fraction over
+    fn pace(&mut self, speed: f32) {
+        self.speed = speed.min(VELOCITY as f32);
+        let travelled = self.carry + self.speed;
+        self.velocity = travelled as i32;
+        self.carry = travelled - self.velocity as f32;
+    }
+
+    fn advance(&mut self) {
         match self.direction {
             Direction::North => self.rect.offset(0, -self.velocity),
             Direction::South => self.rect.offset(0, self.velocity),
@@ -130,7 +419,8 @@ impl Vehicle {
         window_color.b = (window_color.b as f32 * 1.3).min(255.0) as u8;
         
         canvas.set_draw_color(window_color);
-        let window = match self.direction {
+        let heading = self.ring_angle.map(roundabout::heading).unwrap_or(self.direction);
+        let window = match heading {
             Direction::North => Rect::new(
                 self.rect.x() + 2,
                 self.rect.y() + 2,
@@ -157,48 +447,50 @@ impl Vehicle {
             ),
         };
         let _ = canvas.fill_rect(window);
+
+        if self.equipped {
+            // Antenna of a connected vehicle
+            canvas.set_draw_color(Color::RGB(0, 220, 255));
+            let center = self.rect.center();
+            let _ = canvas.fill_rect(Rect::new(center.x - 2, center.y - 2, 4, 4));
+        }
     }
 
     fn should_stop_at_light(&mut self, light_state: LightState) -> bool {
         if self.in_intersection || self.has_turned {
             return false;
         }
-        if light_state == LightState::Green {
-            return false;
+        match light_state {
+            LightState::Green | LightState::FlashingYellow => false,
+            LightState::FlashingRed => self.is_at_stop_line() && self.stopped_frames < FULL_STOP_FRAMES,
+            // Drivers who were nearly there as it turned red may press on
+            LightState::Red => {
+                self.is_at_stop_line() && (self.stopped_frames > 0 || self.red_frames > self.driver.yellow_running)
+            }
         }
+    }
 
+    /// Whether the front of the vehicle has reached its approach's stop line.
+    #[allow(clippy::let_and_return)]
+    pub fn is_at_stop_line(&self) -> bool {
         // Calculate distance to stop line
+        let (cx, cy) = self.center;
         let val = match self.direction {
-            Direction::North => self.rect.y() > 350 && self.rect.y() <= 450,
+            Direction::North => self.rect.y() > cy - 50 && self.rect.y() <= cy + 50,
             Direction::South => {
-                (self.rect.y() + self.rect.height() as i32) < 450
-                    && (self.rect.y() + self.rect.height() as i32) >= 350
+                (self.rect.y() + self.rect.height() as i32) < cy + 50
+                    && (self.rect.y() + self.rect.height() as i32) >= cy - 50
             }
             Direction::East => {
-                (self.rect.x() + self.rect.width() as i32) < 450
-                    && (self.rect.x() + self.rect.width() as i32) >= 350
+                (self.rect.x() + self.rect.width() as i32) < cx + 50
+                    && (self.rect.x() + self.rect.width() as i32) >= cx - 50
             }
-            Direction::West => self.rect.x() > 350 && self.rect.x() <= 450,
+            Direction::West => self.rect.x() > cx - 50 && self.rect.x() <= cx + 50,
         };
         val
     }
     pub fn apply_turn(&mut self) {
-        self.direction = match (self.direction, self.turn) {
-            // Go straight: no change
-            (dir, Turn::Straight) => dir,
-
-            // Right turns
-            (Direction::North, Turn::Right) => Direction::East,
-            (Direction::East, Turn::Right) => Direction::South,
-            (Direction::South, Turn::Right) => Direction::West,
-            (Direction::West, Turn::Right) => Direction::North,
-
-            // Left turns
-            (Direction::North, Turn::Left) => Direction::West,
-            (Direction::West, Turn::Left) => Direction::South,
-            (Direction::South, Turn::Left) => Direction::East,
-            (Direction::East, Turn::Left) => Direction::North,
-        };
+        self.direction = turned_direction(self.direction, self.turn);
         // Swap width/height if we flipped between vertical<->horizontal
         let (w, h) = (self.rect.width(), self.rect.height());
         if (w > h && matches!(self.direction, Direction::North | Direction::South))
@@ -215,5 +507,50 @@ impl Vehicle {
                 new_h,
             );
         }
+        if !self.snaps_to_lane {
+            return;
+        }
+        let (cx, cy) = self.center;
+        match self.direction {
+            Direction::North => self.rect.set_x(cx + 15),
+            Direction::South => self.rect.set_x(cx - 35),
+            Direction::East => self.rect.set_y(cy + 15),
+            Direction::West => self.rect.set_y(cy - 35),
+        }
+    }
+}
+
+pub fn turned_direction(direction: Direction, turn: Turn) -> Direction {
+    match (direction, turn) {
+        // Go straight: no change
+        (dir, Turn::Straight) => dir,
+
+        // Right turns
+        (Direction::North, Turn::Right) => Direction::East,
+        (Direction::East, Turn::Right) => Direction::South,
+        (Direction::South, Turn::Right) => Direction::West,
+        (Direction::West, Turn::Right) => Direction::North,
+
+        // Left turns
+        (Direction::North, Turn::Left) => Direction::West,
+        (Direction::West, Turn::Left) => Direction::South,
+        (Direction::South, Turn::Left) => Direction::East,
+        (Direction::East, Turn::Left) => Direction::North,
+    }
+}
+
+// A vehicle-sized rect facing `direction`, centred on (x, y)
+fn oriented_rect(direction: Direction, x: i32, y: i32) -> Rect {
+    match direction {
+        Direction::North | Direction::South => Rect::new(x - 10, y - 20, 20, 40),
+        Direction::East | Direction::West => Rect::new(x - 20, y - 10, 40, 20),
+    }
+}
+
+fn turn_color(turn: Turn) -> Color {
+    match turn {
+        Turn::Straight => Color::RGB(180, 180, 200),  // Silver/gray for straight
+        Turn::Right => Color::RGB(200, 60, 60),       // Deep red for right turn
+        Turn::Left => Color::RGB(60, 100, 180),       // Deep blue for left turn
     }
 }
@@ -0,0 +1,107 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::coordination::FixedTimePlan;
+use crate::discharge;
+use crate::simulation::FRAMES_PER_SECOND;
+use crate::vehicle::{Direction, Vehicle};
+
+// Phase order of the re-timed plans, one approach served at a time
+const APPROACHES: [Direction; 4] = [Direction::South, Direction::West, Direction::North, Direction::East];
+// Arrivals are counted over the last five minutes
+const WINDOW_FRAMES: i32 = 5 * 60 * FRAMES_PER_SECOND;
+// Every approach keeps a green, however little it sees
+const MIN_GREEN_FRAMES: i32 = 60;
+// Webster's cycle grows without bound as the flow ratios near 1
+const MAX_CYCLE_FRAMES: i32 = 120 * FRAMES_PER_SECOND;
+
+/// Webster's optimal cycle, in frames, for `phases` phases whose critical
+/// flow ratios sum to `y`: (1.5 L + 5) / (1 - Y) seconds, L being the time
+/// lost to start-up over the cycle.
+pub fn optimal_cycle(phases: usize, y: f32) -> i32 {
+    if y >= 1. {
+        return MAX_CYCLE_FRAMES;
+    }
+    let second = FRAMES_PER_SECOND as f32;
+    let lost = phases as f32 * discharge::START_UP_LOST / second;
+    let cycle = (1.5 * lost + 5.) / (1. - y) * second;
+    (cycle.round() as i32).clamp(phases as i32 * MIN_GREEN_FRAMES, MAX_CYCLE_FRAMES)
+}
+
+/// A fixed-time plan serving each approach in turn, from the flow on each in
+/// vehicles per hour: Webster's optimal cycle, its effective green shared out
+/// in proportion to the approaches' flow ratios.
+pub fn plan(flows: &[(Direction, f32)], safe_distance: i32) -> FixedTimePlan {
+    let ratios: Vec<(Direction, f32)> = flows
+        .iter()
+        .map(|&(direction, flow)| (direction, flow / discharge::saturation_flow(safe_distance)))
+        .collect();
+    let y: f32 = ratios.iter().map(|&(_, ratio)| ratio).sum();
+    let cycle = optimal_cycle(ratios.len(), y);
+    let effective = (cycle as f32 - ratios.len() as f32 * discharge::START_UP_LOST).max(0.);
+    let phases: Vec<(Direction, i32)> = ratios
+        .iter()
+        .map(|&(direction, ratio)| {
+            let share = if y > 0. { ratio / y } else { 1. / ratios.len() as f32 };
+            let green = effective * share + discharge::START_UP_LOST;
+            (direction, (green.round() as i32).max(MIN_GREEN_FRAMES))
+        })
+        .collect();
+    FixedTimePlan {
+        cycle: phases.iter().map(|&(_, green)| green).sum(),
+        offset: 0,
+        phases,
+    }
+}
+
+/// Counts the vehicles joining each approach of every intersection, so
+/// fixed-time plans can be re-timed to the demand they see.
+pub struct Webster {
+    // Per intersection, the frame each vehicle arrived and the approach it took
+    arrivals: Vec<VecDeque<(i32, Direction)>>,
+    // Vehicles already counted, with the intersection they were counted at
+    counted: HashSet<(u32, usize)>,
+}
+
+impl Webster {
+    pub fn new(nodes: usize) -> Self {
+        Webster {
+            arrivals: vec![VecDeque::new(); nodes],
+            counted: HashSet::new(),
+        }
+    }
+
+    pub fn observe(&mut self, vehicles: &[Vehicle], frame: i32) {
+        for v in vehicles.iter().filter(|v| !v.in_intersection && !v.has_turned) {
+            if self.counted.insert((v.id, v.node)) {
+                self.arrivals[v.node].push_back((frame, v.direction));
+            }
+        }
+        let present: HashSet<u32> = vehicles.iter().map(|v| v.id).collect();
+        self.counted.retain(|(id, _)| present.contains(id));
+        for arrivals in &mut self.arrivals {
+            while arrivals.front().is_some_and(|&(at, _)| frame - at >= WINDOW_FRAMES) {
+                arrivals.pop_front();
+            }
+        }
+    }
+
+    /// Vehicles per hour that arrived on each approach of `node` lately.
+    pub fn flows(&self, node: usize, frame: i32) -> Vec<(Direction, f32)> {
+        let hours = frame.clamp(1, WINDOW_FRAMES) as f32 / FRAMES_PER_SECOND as f32 / 3600.;
+        APPROACHES
+            .iter()
+            .map(|&direction| {
+                let count = self.arrivals[node].iter().filter(|&&(_, d)| d == direction).count();
+                (direction, count as f32 / hours)
+            })
+            .collect()
+    }
+
+    /// A plan for `node` timed to its recent flows, starting at `frame`.
+    pub fn plan_for(&self, node: usize, frame: i32, safe_distance: i32) -> FixedTimePlan {
+        FixedTimePlan {
+            offset: frame,
+            ..plan(&self.flows(node, frame), safe_distance)
+        }
+    }
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use road_intersection::calibration::{self, LaneGroup};
+use road_intersection::discharge::saturation_headway;
+use road_intersection::simulation::SAFE_DISTANCE;
+use road_intersection::vehicle::Direction;
+
+fn measure(options: &[(&str, &str)]) -> Vec<LaneGroup> {
+    let mut options = common::options(options);
+    options.entry("turns".to_string()).or_insert_with(|| "1,0,0".to_string());
+    calibration::measure(&options, 3, 2).unwrap()
+}
+
+#[test]
+fn straight_queues_discharge_at_the_modelled_headway() {
+    for group in measure(&[]) {
+        let headway = group.saturation_headway().unwrap();
+        assert!((headway - saturation_headway(SAFE_DISTANCE)).abs() < 1., "{:?}: {}", group.direction, headway);
+        assert_eq!(group.capacity(), group.saturation_flow());
+    }
+}
+
+#[test]
+fn fixed_plan_capacity_follows_green_splits() {
+    let groups = measure(&[("plan", "fixed")]);
+    let east = groups.iter().find(|g| g.direction == Direction::East).unwrap();
+    for group in &groups {
+        let capacity = group.capacity().unwrap();
+        assert!((capacity - group.saturation_flow().unwrap() * group.green_share).abs() < 1e-3);
+        if group.direction != Direction::East {
+            assert!(group.green_share < east.green_share);
+        }
+    }
+    let total: f32 = groups.iter().map(|g| g.green_share).sum();
+    assert!((total - 1.).abs() < 1e-3);
+}
+
+#[test]
+fn suggested_safe_distance_reproduces_field_flow() {
+    let field_flow = 2500.;
+    let headway = measure(&[])[0].saturation_headway().unwrap();
+    let distance = calibration::safe_distance_for(headway, SAFE_DISTANCE, field_flow);
+    assert!(distance > SAFE_DISTANCE);
+
+    let distance = distance.to_string();
+    let calibrated = measure(&[("safe-distance", &distance)]);
+    let flow = calibrated[0].saturation_flow().unwrap();
+    assert!((flow - field_flow).abs() / field_flow < 0.05, "{}", flow);
+}
+
+#[test]
+fn bad_safe_distances_are_rejected() {
+    let options = common::options(&[("safe-distance", "far")]);
+    assert!(calibration::measure(&options, 3, 1).is_err());
+}
@@ -0,0 +1,58 @@
+use road_intersection::intersection::Controller;
+use road_intersection::network::Network;
+use road_intersection::simulation::Simulation;
+use road_intersection::vehicle::{Direction, Turn, Vehicle};
+
+// A vehicle that has turned east, left across the northbound lane south of
+// the box, overlapping a northbound vehicle
+fn tangled() -> Simulation {
+    let mut sim = Simulation::new(Network::single(), 1);
+    sim.set_controller(|| Controller::External);
+    sim.intersections[0].set_green(Some(Direction::East));
+    let mut crossing = Vehicle::new(1, Direction::East, 0, (400, 400), 440, Turn::Straight);
+    crossing.rect.set_y(500);
+    crossing.has_turned = true;
+    let northbound = Vehicle::new(2, Direction::North, 0, (400, 400), 490, Turn::Straight);
+    assert!(crossing.rect.has_intersection(northbound.rect));
+    sim.vehicles = vec![crossing, northbound];
+    sim
+}
+
+#[test]
+fn only_the_leader_of_an_overlapping_pair_moves() {
+    let mut sim = tangled();
+    let before: Vec<_> = sim.vehicles.iter().map(|v| v.rect).collect();
+    sim.step();
+    assert_ne!(sim.vehicles[0].rect, before[0]);
+    assert_eq!(sim.vehicles[1].rect, before[1]);
+
+    // Once clear, the other goes on its way
+    while sim.vehicles[0].rect.has_intersection(sim.vehicles[1].rect) {
+        assert_eq!(sim.vehicles[1].rect, before[1]);
+        sim.step();
+    }
+    for _ in 0..5 {
+        sim.step();
+    }
+    assert_ne!(sim.vehicles[1].rect, before[1]);
+}
+
+#[test]
+fn the_older_vehicle_goes_first_into_contested_space() {
+    // Both about to move into the same spot, neither there yet
+    let mut sim = Simulation::new(Network::single(), 1);
+    sim.set_controller(|| Controller::External);
+    sim.intersections[0].set_green(Some(Direction::East));
+    let mut crossing = Vehicle::new(1, Direction::East, 0, (400, 400), 413, Turn::Straight);
+    crossing.rect.set_y(500);
+    crossing.has_turned = true;
+    crossing.has_turned = true;
+    let northbound = Vehicle::new(2, Direction::North, 0, (400, 400), 521, Turn::Straight);
+    assert!(!crossing.rect.has_intersection(northbound.rect));
+    sim.vehicles = vec![crossing, northbound];
+    let before: Vec<_> = sim.vehicles.iter().map(|v| v.rect).collect();
+    sim.step();
+    assert_ne!(sim.vehicles[0].rect, before[0]);
+    assert_eq!(sim.vehicles[1].rect, before[1]);
+    assert!(!sim.vehicles[0].rect.has_intersection(sim.vehicles[1].rect));
+}
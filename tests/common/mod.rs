@@ -0,0 +1,18 @@
+// Shared by the integration tests; each uses only part of it
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use road_intersection::scenario;
+use road_intersection::simulation::Simulation;
+
+/// Command-line style `--name value` options from `(name, value)` pairs.
+pub fn options(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+/// The scenario `layout` (e.g. `"grid"`, `"corridor 3"`) built with `options`.
+pub fn build(layout: &str, options: &[(&str, &str)], seed: u64) -> Simulation {
+    let layout: Vec<String> = layout.split_whitespace().map(String::from).collect();
+    scenario::build(&layout, &self::options(options), seed).unwrap()
+}
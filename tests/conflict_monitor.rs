@@ -0,0 +1,154 @@
+mod common;
+
+use road_intersection::conflict_monitor::{compatible, conflicting_greens};
+use road_intersection::env::Env;
+use road_intersection::intersection::Controller;
+use road_intersection::safety::SafetyEvent;
+use road_intersection::simulation::Simulation;
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::{Direction, Turn};
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+const TURNS: [Turn; 3] = [Turn::Straight, Turn::Right, Turn::Left];
+const FRAMES: i32 = 6000;
+
+fn build(layout: &str, options: &[(&str, &str)]) -> Simulation {
+    build_seeded(layout, options, 7)
+}
+
+fn build_seeded(layout: &str, options: &[(&str, &str)], seed: u64) -> Simulation {
+    let mut options = options.to_vec();
+    if !options.iter().any(|&(name, _)| name == "demand") {
+        options.push(("demand", "0.6"));
+    }
+    common::build(layout, &options, seed)
+}
+
+fn is_fault(event: &SafetyEvent) -> bool {
+    matches!(event, SafetyEvent::ConflictingGreen { .. })
+}
+
+// Steps `sim`, failing on any conflicting green, and checks no intersection flashes
+fn run_clean(sim: &mut Simulation, frames: i32) {
+    for _ in 0..frames {
+        for event in sim.step() {
+            assert!(!is_fault(&event), "{}", event);
+        }
+        for intersection in &sim.intersections {
+            assert_eq!(conflicting_greens(intersection), None);
+        }
+    }
+    assert!(sim
+        .intersections
+        .iter()
+        .all(|i| !matches!(i.controller, Controller::Flash(_))));
+}
+
+#[test]
+fn matrix_is_symmetric() {
+    for &a in &HEADINGS {
+        for &ta in &TURNS {
+            for &b in &HEADINGS {
+                for &tb in &TURNS {
+                    assert_eq!(compatible((a, ta), (b, tb)), compatible((b, tb), (a, ta)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn matrix_separates_crossing_movements() {
+    assert!(compatible((Direction::North, Turn::Straight), (Direction::South, Turn::Straight)));
+    assert!(!compatible((Direction::North, Turn::Straight), (Direction::East, Turn::Straight)));
+    assert!(!compatible((Direction::North, Turn::Left), (Direction::South, Turn::Straight)));
+}
+
+#[test]
+fn adaptive_respects_matrix() {
+    run_clean(&mut build("grid", &[]), FRAMES);
+}
+
+#[test]
+fn fixed_time_respects_matrix() {
+    run_clean(&mut build("corridor", &[("plan", "fixed")]), FRAMES);
+}
+
+#[test]
+fn green_wave_respects_matrix() {
+    run_clean(&mut build("corridor", &[("plan", "green-wave")]), FRAMES);
+}
+
+#[test]
+fn max_pressure_respects_matrix() {
+    run_clean(&mut build("grid", &[("plan", "max-pressure")]), FRAMES);
+}
+
+#[test]
+fn longest_queue_respects_matrix() {
+    run_clean(&mut build("grid", &[("plan", "longest-queue")]), FRAMES);
+}
+
+#[test]
+fn right_turn_on_red_respects_matrix() {
+    run_clean(&mut build("single", &[("rtor", "on")]), FRAMES);
+}
+
+#[test]
+fn manual_respects_matrix() {
+    let mut sim = build("single", &[("plan", "fixed")]);
+    run_clean(&mut sim, 100);
+    let frame = sim.frame_count;
+    sim.intersections[0].toggle_manual(frame);
+    // Every toggle either waits for clearance or is refused, never a conflict
+    for (i, &direction) in HEADINGS.iter().cycle().take(12).enumerate() {
+        let frame = sim.frame_count;
+        if let Controller::Manual(manual) = &mut sim.intersections[0].controller {
+            let _ = manual.toggle(direction, frame);
+            if i % 3 == 0 {
+                let _ = manual.toggle(direction, frame);
+            }
+        }
+        run_clean(&mut sim, 150);
+    }
+}
+
+#[test]
+fn external_respects_matrix() {
+    let mut env = Env::new(|seed| build_seeded("corridor", &[], seed), 1800);
+    env.reset(3);
+    for i in 0.. {
+        let actions = vec![HEADINGS[i % 4]; 3];
+        let (observation, _, done) = env.step(&actions).unwrap();
+        for lights in &observation.lights {
+            let greens = lights.iter().filter(|l| **l == LightState::Green);
+            assert!(greens.count() <= 1);
+        }
+        if done {
+            break;
+        }
+    }
+}
+
+#[test]
+fn conflicting_greens_trip_flash() {
+    let mut sim = build("single", &[("plan", "fixed")]);
+    run_clean(&mut sim, 100);
+    let intersection = &mut sim.intersections[0];
+    intersection.controller = Controller::External;
+    intersection.light_s.update(true);
+    intersection.light_w.update(true);
+
+    let faults: Vec<SafetyEvent> = sim.step().into_iter().filter(is_fault).collect();
+    assert_eq!(faults.len(), 1);
+    assert!(matches!(sim.intersections[0].controller, Controller::Flash(_)));
+    assert_eq!(conflicting_greens(&sim.intersections[0]), None);
+
+    // Traffic keeps flowing through the all-way stop
+    let before = sim.metrics.total.vehicles;
+    for _ in 0..FRAMES {
+        assert!(!sim.step().iter().any(is_fault));
+    }
+    assert!(sim.metrics.total.vehicles > before);
+    assert!(matches!(sim.intersections[0].controller, Controller::Flash(_)));
+}
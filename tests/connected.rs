@@ -0,0 +1,58 @@
+mod common;
+
+use road_intersection::connected::{advise, Spat};
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::{Direction, Turn, Vehicle};
+
+fn spat(state: LightState, remaining: Option<i32>, next: Option<LightState>) -> Spat {
+    Spat {
+        node: 0,
+        direction: Direction::North,
+        state,
+        remaining,
+        next,
+    }
+}
+
+// A northbound vehicle 350 pixels before the box
+fn approaching() -> Vehicle {
+    let vehicle = Vehicle::new(1, Direction::North, 0, (400, 400), 800, Turn::Straight);
+    assert_eq!(vehicle.distance_to_box(), 350);
+    vehicle
+}
+
+#[test]
+fn advises_arriving_as_red_turns_green() {
+    let vehicle = approaching();
+    let speed = advise(&vehicle, &spat(LightState::Red, Some(100), Some(LightState::Green))).unwrap();
+    assert!((speed - 3.5).abs() < 1e-6);
+}
+
+#[test]
+fn no_advice_when_it_would_not_help() {
+    let vehicle = approaching();
+    // Reaches the box after the light has turned green anyway
+    assert_eq!(advise(&vehicle, &spat(LightState::Red, Some(60), Some(LightState::Green))), None);
+    // Would have to crawl: better to stop
+    assert_eq!(advise(&vehicle, &spat(LightState::Red, Some(1000), Some(LightState::Green))), None);
+    // Timing unknown
+    assert_eq!(advise(&vehicle, &spat(LightState::Red, None, None)), None);
+    assert_eq!(advise(&vehicle, &spat(LightState::Green, Some(10), Some(LightState::Red))), None);
+}
+
+fn stops_per_vehicle(penetration: &str) -> (f32, u32) {
+    let mut sim = common::build("corridor", &[("plan", "fixed"), ("demand", "4"), ("penetration", penetration)], 3);
+    for _ in 0..18000 {
+        sim.step();
+    }
+    (sim.metrics.total.stops_per_vehicle(), sim.metrics.equipped.vehicles)
+}
+
+#[test]
+fn equipped_vehicles_stop_less() {
+    let (unequipped, none) = stops_per_vehicle("0");
+    let (equipped, all) = stops_per_vehicle("1");
+    assert_eq!(none, 0);
+    assert!(all > 0);
+    assert!(equipped < unequipped);
+}
@@ -0,0 +1,66 @@
+use road_intersection::coordination::{green_wave, green_wave_offsets, FixedTimePlan};
+use road_intersection::network::{Network, BLOCK_SPACING};
+use road_intersection::simulation::Simulation;
+use road_intersection::vehicle::{Direction, VELOCITY};
+
+#[test]
+fn offsets_follow_travel_time_between_nodes() {
+    let corridor = Network::corridor(3);
+    let speed = VELOCITY as f32;
+    let hop = BLOCK_SPACING / VELOCITY;
+    let cycle = 80;
+    assert_eq!(
+        green_wave_offsets(&corridor, Direction::East, speed, cycle),
+        vec![0, hop % cycle, 2 * hop % cycle]
+    );
+    // Heading west, the wave starts from the far end
+    assert_eq!(
+        green_wave_offsets(&corridor, Direction::West, speed, cycle),
+        vec![2 * hop % cycle, hop % cycle, 0]
+    );
+    // Nodes level with each other across the wave go green together
+    let grid = Network::grid(2, 2);
+    assert_eq!(green_wave_offsets(&grid, Direction::East, speed, cycle), vec![0, hop, 0, hop]);
+
+    let plans = green_wave(&corridor, Direction::East, speed, 100, 50);
+    assert!(plans.iter().all(|plan| plan.cycle == plans[0].cycle && plan.phases == plans[0].phases));
+    let offsets: Vec<i32> = plans.iter().map(|plan| plan.offset).collect();
+    assert_eq!(offsets, green_wave_offsets(&corridor, Direction::East, speed, plans[0].cycle));
+}
+
+#[test]
+fn phases_wrap_around_the_cycle() {
+    // East 100, South 50, West 50, North 50
+    let mut plan = FixedTimePlan::new(Direction::East, 100, 50);
+    assert_eq!(plan.cycle, 250);
+    assert_eq!(plan.phase_at(0), Direction::East);
+    assert_eq!(plan.phase_at(100), Direction::South);
+    assert_eq!(plan.phase_at(249), Direction::North);
+    assert_eq!(plan.phase_at(250), Direction::East);
+    assert_eq!(plan.phase_at(-1), Direction::North);
+    assert_eq!(plan.phase_at(-250), Direction::East);
+    assert_eq!(plan.remaining_at(-1), 1);
+
+    plan.offset = 30;
+    assert_eq!(plan.phase_at(30), Direction::East);
+    assert_eq!(plan.phase_at(29), Direction::North);
+    assert_eq!(plan.phase_at(130), Direction::South);
+    assert_eq!(plan.phase_at(30 + 7 * 250), Direction::East);
+    assert_eq!(plan.phase_at(-220), Direction::East);
+    assert_eq!(plan.remaining_at(29), 1);
+    assert_eq!(plan.until_green(Direction::East, 29), Some(1));
+}
+
+#[test]
+fn refused_spawns_take_no_id() {
+    let mut sim = Simulation::new(Network::single(), 1);
+    assert!(sim.spawn_at(Direction::North, 0));
+    // Too close behind the first one
+    assert!(!sim.spawn_at(Direction::North, 0));
+    for _ in 0..60 {
+        sim.step();
+    }
+    assert!(sim.spawn_at(Direction::North, 0));
+    let ids: Vec<u32> = sim.vehicles.iter().map(|v| v.id).collect();
+    assert_eq!(ids, [1, 2]);
+}
@@ -0,0 +1,110 @@
+use road_intersection::discharge::{self, saturation_headway};
+use road_intersection::driver::STARTUP_REACTION_FRAMES;
+use road_intersection::intersection::Controller;
+use road_intersection::network::Network;
+use road_intersection::simulation::{Simulation, SAFE_DISTANCE};
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::{Direction, TurnRatios};
+
+// Frames after the light turns green at which each of a standing queue of
+// `queue` northbound vehicles, `safe_distance` apart, enters the box
+fn entries(queue: usize, safe_distance: i32) -> Vec<i32> {
+    let mut sim = Simulation::new(Network::single(), 1);
+    sim.safe_distance = safe_distance;
+    sim.set_controller(|| Controller::External);
+    sim.intersections[0].set_green(None);
+    // Going straight on, nobody waits for a turning vehicle to clear the box
+    sim.turn_ratios = TurnRatios {
+        straight: 1.,
+        right: 0.,
+        left: 0.,
+    };
+    while sim.vehicles.len() < queue {
+        sim.spawn(Direction::North);
+        sim.step();
+    }
+    // Let the whole queue come to rest at the red light
+    for _ in 0..600 {
+        sim.step();
+    }
+    assert!(sim.vehicles.iter().all(|v| v.at_standstill()));
+
+    sim.intersections[0].set_green(Some(Direction::North));
+    let ids: Vec<u32> = sim.vehicles.iter().map(|v| v.id).collect();
+    let mut entered = vec![None; queue];
+    for frame in 1..=1200 {
+        sim.step();
+        for (i, id) in ids.iter().enumerate() {
+            let inside = sim.vehicles.iter().find(|v| v.id == *id).is_none_or(|v| v.in_intersection);
+            if inside && entered[i].is_none() {
+                entered[i] = Some(frame);
+            }
+        }
+    }
+    entered.into_iter().map(Option::unwrap).collect()
+}
+
+#[test]
+fn queue_discharges_one_after_another() {
+    for safe_distance in [SAFE_DISTANCE, SAFE_DISTANCE / 2] {
+        let entered = entries(4, safe_distance);
+        // The head of the queue reacts before moving off
+        assert!(entered[0] > STARTUP_REACTION_FRAMES);
+        for pair in entered.windows(2) {
+            assert!(pair[1] > pair[0], "{:?}", entered);
+        }
+        // Once the queue is moving, vehicles follow at the saturation headway
+        for pair in entered[2..].windows(2) {
+            let headway = (pair[1] - pair[0]) as f32;
+            assert!((headway - saturation_headway(safe_distance)).abs() <= 3., "{:?}", entered);
+        }
+    }
+}
+
+#[test]
+fn green_covers_the_queue_discharge() {
+    for safe_distance in [SAFE_DISTANCE, SAFE_DISTANCE / 2] {
+        assert_eq!(discharge::green_frames(0, safe_distance), 0);
+        let entered = entries(4, safe_distance);
+        let green = discharge::green_frames(4, safe_distance);
+        assert!(green >= entered[3]);
+        assert!(green - entered[3] < saturation_headway(safe_distance) as i32);
+    }
+}
+
+#[test]
+fn vehicles_still_driving_in_clear_on_one_green() {
+    let mut sim = Simulation::new(Network::single(), 1);
+    sim.set_controller(|| Controller::External);
+    sim.intersections[0].set_green(None);
+    sim.turn_ratios = TurnRatios {
+        straight: 1.,
+        right: 0.,
+        left: 0.,
+    };
+    // Two vehicles well spread out along the approach, neither stopped yet
+    assert!(sim.spawn_at(Direction::North, 0));
+    for _ in 0..30 {
+        sim.step();
+    }
+    assert!(sim.spawn_at(Direction::North, 0));
+    for _ in 0..5 {
+        sim.step();
+    }
+    assert!(sim.vehicles.iter().all(|v| v.stopped_frames == 0));
+
+    sim.set_controller(|| Controller::Adaptive);
+    let ids: Vec<u32> = sim.vehicles.iter().map(|v| v.id).collect();
+    let mut greens = 0;
+    let mut was_green = false;
+    while sim.vehicles.iter().any(|v| ids.contains(&v.id) && !v.in_intersection) {
+        sim.step();
+        let green = sim.intersections[0].light_for(Direction::North) == LightState::Green;
+        if green && !was_green {
+            greens += 1;
+        }
+        was_green = green;
+        assert!(sim.frame_count < 1200, "never cleared");
+    }
+    assert_eq!(greens, 1);
+}
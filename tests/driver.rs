@@ -0,0 +1,110 @@
+mod common;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use road_intersection::driver::{Driver, NOMINAL_GAP_FRAMES, STARTUP_REACTION_FRAMES};
+use road_intersection::safety::SafetyEvent;
+use road_intersection::simulation::FRAMES_PER_SECOND;
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::{Direction, Turn, Vehicle, VELOCITY};
+
+// A northbound vehicle driven up to its stop line on green, still moving
+fn at_stop_line(driver: Driver) -> Vehicle {
+    let mut vehicle = Vehicle::new(1, Direction::North, 0, (400, 400), 800, Turn::Straight);
+    vehicle.driver = driver;
+    while vehicle.distance_to_box() > 0 {
+        vehicle.update(LightState::Green);
+        vehicle.track_motion(true);
+    }
+    vehicle
+}
+
+#[test]
+fn default_driver_is_the_average_driver() {
+    let driver = Driver::default();
+    assert_eq!(driver.reaction_frames, STARTUP_REACTION_FRAMES);
+    assert_eq!(driver.desired_speed, VELOCITY as f32);
+    assert_eq!(driver.acceptable_gap, NOMINAL_GAP_FRAMES);
+    assert_eq!(driver.yellow_running, 0);
+}
+
+#[test]
+fn sampled_drivers_stay_in_range() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let second = FRAMES_PER_SECOND as f32;
+    let drivers: Vec<Driver> = (0..2000).map(|_| Driver::sample(&mut rng)).collect();
+    for d in &drivers {
+        assert!((0.2 * second) as i32 <= d.reaction_frames && d.reaction_frames <= (0.8 * second) as i32);
+        assert!(0.75 * VELOCITY as f32 <= d.desired_speed && d.desired_speed <= VELOCITY as f32);
+        assert!((0.7 * second) as i32 <= d.acceptable_gap && d.acceptable_gap <= (1.5 * second) as i32);
+        assert!((0..=FRAMES_PER_SECOND / 2).contains(&d.yellow_running));
+    }
+    let runners = drivers.iter().filter(|d| d.yellow_running > 0).count();
+    assert!((400..600).contains(&runners), "{} red runners", runners);
+}
+
+#[test]
+fn red_runner_presses_on_as_the_light_changes() {
+    let mut cautious = at_stop_line(Driver::default());
+    let before = cautious.rect;
+    cautious.update(LightState::Red);
+    assert_eq!(cautious.rect, before);
+
+    let mut runner = at_stop_line(Driver {
+        yellow_running: 10,
+        ..Driver::default()
+    });
+    let before = runner.rect;
+    runner.update(LightState::Red);
+    assert_ne!(runner.rect, before);
+}
+
+#[test]
+fn reaction_time_delays_moving_off() {
+    let mut vehicle = at_stop_line(Driver {
+        reaction_frames: 12,
+        ..Driver::default()
+    });
+    for _ in 0..30 {
+        vehicle.update(LightState::Red);
+        vehicle.track_motion(false);
+    }
+    let stopped = vehicle.rect;
+    for _ in 0..12 {
+        vehicle.update(LightState::Green);
+        assert_eq!(vehicle.rect, stopped);
+        vehicle.track_motion(false);
+    }
+    vehicle.update(LightState::Green);
+    assert_ne!(vehicle.rect, stopped);
+}
+
+// Red light entries and average travel time over three hours at a fixed-time junction
+fn run(drivers: &str) -> (usize, f32) {
+    let mut sim = common::build("single", &[("plan", "fixed"), ("demand", "4"), ("drivers", drivers)], 5);
+    let mut entries = 0;
+    for _ in 0..10800 {
+        entries += sim
+            .step()
+            .iter()
+            .filter(|event| matches!(event, SafetyEvent::RedLightEntry { .. }))
+            .count();
+    }
+    assert!(sim.metrics.total.vehicles > 0);
+    (entries, sim.metrics.total.average_travel_time())
+}
+
+#[test]
+fn only_varied_drivers_run_red_lights() {
+    assert_eq!(run("uniform").0, 0);
+    assert!(run("varied").0 > 0);
+}
+
+#[test]
+fn varied_drivers_lose_more_time() {
+    // Measured in travel time rather than standstill delay: every driver now
+    // reacts before moving off, and slower cruising or running a red changes
+    // how long is spent moving as much as how long is spent waiting
+    let (uniform, varied) = (run("uniform").1, run("varied").1);
+    assert!(varied > uniform, "{} against {}", varied, uniform);
+}
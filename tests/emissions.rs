@@ -0,0 +1,61 @@
+mod common;
+
+use road_intersection::emissions::Emissions;
+use road_intersection::vehicle::VELOCITY;
+
+// Fuel burnt over `speeds`, one per frame, in pixels
+fn fuel(speeds: impl IntoIterator<Item = f64>) -> f64 {
+    let mut emissions = Emissions::default();
+    for speed in speeds {
+        emissions.record(speed);
+    }
+    emissions.fuel
+}
+
+#[test]
+fn idling_burns_fuel() {
+    let idle = fuel(std::iter::repeat_n(0., 3600));
+    assert!(idle > 0.);
+    // Longer idling burns proportionally more
+    assert!((fuel(std::iter::repeat_n(0., 7200)) - 2. * idle).abs() < 1e-9);
+}
+
+#[test]
+fn stopping_costs_more_than_cruising() {
+    let cruise = std::iter::repeat_n(VELOCITY as f64, 600);
+    let with_stop = std::iter::repeat_n(VELOCITY as f64, 300)
+        .chain(std::iter::repeat_n(0., 60))
+        .chain(std::iter::repeat_n(VELOCITY as f64, 300));
+    assert!(fuel(with_stop) > fuel(cruise));
+}
+
+#[test]
+fn steady_slowing_beats_stopping() {
+    // Covering the same ground in the same time, with and without a stop
+    let slow = std::iter::repeat_n(VELOCITY as f64 / 2., 120);
+    let stop = std::iter::repeat_n(VELOCITY as f64, 60).chain(std::iter::repeat_n(0., 60));
+    let resume = std::iter::repeat_n(VELOCITY as f64, 60);
+    assert!(fuel(slow.chain(resume.clone())) < fuel(stop.chain(resume)));
+}
+
+#[test]
+fn co2_follows_fuel() {
+    let mut emissions = Emissions::default();
+    for _ in 0..600 {
+        emissions.record(VELOCITY as f64);
+    }
+    assert!((emissions.co2 / emissions.fuel - 2310.).abs() < 1e-6);
+}
+
+#[test]
+fn network_totals_include_vehicles_still_travelling() {
+    let mut sim = common::build("single", &[("demand", "10")], 1);
+    for _ in 0..1800 {
+        sim.step();
+    }
+    assert!(!sim.vehicles.is_empty());
+    let travelling: f64 = sim.vehicles.iter().map(|v| v.emissions.fuel).sum();
+    assert!(sim.metrics.fuel > sim.metrics.total.fuel);
+    assert!((sim.metrics.fuel - sim.metrics.total.fuel - travelling).abs() < 1e-9);
+    assert!((sim.metrics.co2 / sim.metrics.fuel - 2310.).abs() < 1e-6);
+}
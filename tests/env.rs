@@ -0,0 +1,66 @@
+use road_intersection::env::{Env, STEP_FRAMES};
+use road_intersection::network::Network;
+use road_intersection::simulation::Simulation;
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::Direction;
+
+const HORIZON: i32 = 10 * STEP_FRAMES;
+
+fn env() -> Env<impl Fn(u64) -> Simulation> {
+    Env::new(
+        |seed| {
+            let mut sim = Simulation::new(Network::grid(2, 1), seed);
+            sim.demand = 20.;
+            sim
+        },
+        HORIZON,
+    )
+}
+
+#[test]
+fn reset_turns_every_light_red() {
+    let mut env = env();
+    env.step(&[Direction::North, Direction::East]).unwrap();
+    let observation = env.reset(3);
+    assert_eq!(observation.lights.len(), 2);
+    assert!(observation.lights.iter().flatten().all(|&light| light == LightState::Red));
+    assert_eq!(observation.total_queued(), 0);
+}
+
+#[test]
+fn steps_need_one_action_per_intersection() {
+    let mut env = env();
+    assert!(env.step(&[Direction::North]).is_err());
+    assert!(env.step(&[Direction::North, Direction::North, Direction::North]).is_err());
+    let (observation, _, _) = env.step(&[Direction::North, Direction::West]).unwrap();
+    assert_eq!(observation.lights[0][0], LightState::Green);
+    assert_eq!(observation.lights[1][3], LightState::Green);
+    assert_eq!(observation.lights[1][0], LightState::Red);
+}
+
+#[test]
+fn reward_is_minus_the_queued_vehicles() {
+    let mut env = env();
+    let mut rewards = Vec::new();
+    for _ in 0..5 {
+        let (observation, reward, _) = env.step(&[Direction::North, Direction::North]).unwrap();
+        assert_eq!(reward, -observation.total_queued() as f32);
+        let queued: i32 = observation.queues.iter().flatten().sum();
+        assert_eq!(observation.total_queued(), queued);
+        rewards.push(reward);
+    }
+    // Only one approach of each intersection is ever served, so queues build up
+    assert!(rewards.iter().any(|&reward| reward < 0.), "{:?}", rewards);
+}
+
+#[test]
+fn episodes_end_at_the_horizon() {
+    let mut env = env();
+    for step in 1..=HORIZON / STEP_FRAMES {
+        let (_, _, done) = env.step(&[Direction::East, Direction::East]).unwrap();
+        assert_eq!(done, step == HORIZON / STEP_FRAMES, "step {}", step);
+    }
+    env.reset(1);
+    let (_, _, done) = env.step(&[Direction::East, Direction::East]).unwrap();
+    assert!(!done);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use road_intersection::flash::FlashCause;
+use road_intersection::intersection::Controller;
+use road_intersection::simulation::{Simulation, FRAMES_PER_SECOND};
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::{Direction, Turn, Vehicle};
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+fn build(layout: &str, night_flash: &str) -> Simulation {
+    common::build(layout, &[("demand", "3"), ("night-flash", night_flash)], 5)
+}
+
+fn is_flashing(sim: &Simulation, cause: FlashCause) -> bool {
+    sim.intersections
+        .iter()
+        .all(|i| matches!(&i.controller, Controller::Flash(flash) if flash.cause == cause))
+}
+
+// Drives a lone northbound vehicle towards the box under `light` and
+// returns the frames it stood still before entering it, if it did
+fn frames_held(light: LightState) -> Option<i32> {
+    let mut vehicle = Vehicle::new(1, Direction::North, 0, (400, 400), 800, Turn::Straight);
+    let mut held = 0;
+    for _ in 0..600 {
+        let before = vehicle.rect;
+        vehicle.update(light);
+        let moved = vehicle.rect != before;
+        vehicle.track_motion(moved);
+        if !moved {
+            held += 1;
+        }
+        if vehicle.in_intersection {
+            return Some(held);
+        }
+    }
+    None
+}
+
+#[test]
+fn flashing_red_is_a_stop_sign() {
+    assert_eq!(frames_held(LightState::Green), Some(0));
+    assert_eq!(frames_held(LightState::FlashingYellow), Some(0));
+    assert!(frames_held(LightState::FlashingRed).is_some_and(|held| held >= 30));
+    assert_eq!(frames_held(LightState::Red), None);
+}
+
+#[test]
+fn night_flash_follows_schedule() {
+    let mut sim = build("grid", "10,40");
+    while sim.frame_count < 20 * FRAMES_PER_SECOND {
+        sim.step();
+    }
+    assert!(is_flashing(&sim, FlashCause::Night));
+    let intersection = &sim.intersections[0];
+    assert_eq!(intersection.light_for(Direction::East), LightState::FlashingYellow);
+    assert_eq!(intersection.light_for(Direction::West), LightState::FlashingYellow);
+    assert_eq!(intersection.light_for(Direction::North), LightState::FlashingRed);
+    assert_eq!(intersection.light_for(Direction::South), LightState::FlashingRed);
+
+    let completed = sim.metrics.total.vehicles;
+    while sim.frame_count < 40 * FRAMES_PER_SECOND {
+        sim.step();
+    }
+    assert!(sim.metrics.total.vehicles > completed);
+
+    sim.step();
+    assert!(sim
+        .intersections
+        .iter()
+        .all(|i| matches!(i.controller, Controller::Adaptive)));
+}
+
+#[test]
+fn fault_outlasts_night_flash() {
+    let mut sim = build("single", "0,10");
+    sim.step();
+    sim.intersections[0].start_flash(FlashCause::Fault);
+    assert!(is_flashing(&sim, FlashCause::Fault));
+    assert!(HEADINGS.iter().all(|&d| sim.intersections[0].light_for(d) == LightState::FlashingRed));
+    while sim.frame_count < 20 * FRAMES_PER_SECOND {
+        sim.step();
+    }
+    assert!(is_flashing(&sim, FlashCause::Fault));
+}
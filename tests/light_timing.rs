@@ -0,0 +1,60 @@
+mod common;
+
+use std::collections::HashMap;
+
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::Direction;
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+// Runs `plan` and checks every announced change happens on the announced
+// frame; returns how many were checked
+fn check_announcements(plan: &str, frames: i32) -> usize {
+    let mut sim = common::build("corridor", &[("plan", plan), ("demand", "3")], 11);
+    // (node, heading) -> frame the light is due to change, and to what
+    let mut due: HashMap<(usize, Direction), (i32, LightState)> = HashMap::new();
+    let mut checked = 0;
+    for _ in 0..frames {
+        sim.step();
+        for intersection in &sim.intersections {
+            for direction in HEADINGS {
+                let key = (intersection.node, direction);
+                let light = intersection.light(direction);
+                if let Some(&(frame, next)) = due.get(&key) {
+                    if frame == sim.frame_count {
+                        assert_eq!(light.state, next, "{:?} at {}", key, frame);
+                        checked += 1;
+                        due.remove(&key);
+                    } else {
+                        assert_ne!(light.state, next, "{:?} changed early at {}", key, sim.frame_count);
+                    }
+                }
+                if let (Some(remaining), Some(next)) = (light.remaining(), light.next_state()) {
+                    assert!(remaining > 0);
+                    due.insert(key, (sim.frame_count + remaining, next));
+                }
+            }
+        }
+    }
+    checked
+}
+
+#[test]
+fn fixed_time_changes_when_announced() {
+    assert!(check_announcements("fixed", 3000) > 50);
+}
+
+#[test]
+fn green_wave_changes_when_announced() {
+    assert!(check_announcements("green-wave", 3000) > 50);
+}
+
+#[test]
+fn adaptive_greens_end_when_announced() {
+    assert!(check_announcements("adaptive", 3000) > 5);
+}
+
+#[test]
+fn queue_control_announces_nothing() {
+    assert_eq!(check_announcements("max-pressure", 3000), 0);
+}
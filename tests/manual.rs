@@ -0,0 +1,68 @@
+use road_intersection::intersection::{Controller, Intersection};
+use road_intersection::manual::{ManualControl, CLEARANCE_FRAMES};
+use road_intersection::network::Network;
+use road_intersection::simulation::Simulation;
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::Direction;
+
+// Operator control taken over at `frame` with only northbound traffic green
+fn north_green(frame: i32) -> ManualControl {
+    ManualControl::new(Controller::External, vec![Direction::North], frame)
+}
+
+fn manual(intersection: &Intersection) -> &ManualControl {
+    match &intersection.controller {
+        Controller::Manual(manual) => manual,
+        _ => panic!("intersection is not under manual control"),
+    }
+}
+
+#[test]
+fn toggling_takes_over_and_hands_back() {
+    let mut sim = Simulation::new(Network::single(), 1);
+    while sim.intersections[0].green_direction().is_none() {
+        sim.step();
+    }
+    let green = sim.intersections[0].green_direction().unwrap();
+    sim.intersections[0].toggle_manual(sim.frame_count);
+    assert_eq!(manual(&sim.intersections[0]).greens(), [green]);
+    // The operator holds the lights as they were
+    for _ in 0..600 {
+        sim.step();
+        assert_eq!(sim.intersections[0].light_for(green), LightState::Green);
+    }
+    sim.intersections[0].toggle_manual(sim.frame_count);
+    assert!(matches!(sim.intersections[0].controller, Controller::Adaptive));
+}
+
+#[test]
+fn conflicting_greens_are_refused() {
+    let mut manual = north_green(0);
+    let refusal = manual.toggle(Direction::East, 10).unwrap_err();
+    assert!(refusal.contains("East") && refusal.contains("North"), "{}", refusal);
+    manual.update(1000);
+    assert_eq!(manual.greens(), [Direction::North]);
+    assert!(manual.waiting().is_empty());
+}
+
+#[test]
+fn greens_wait_for_the_clearance_interval() {
+    let mut manual = north_green(0);
+    // Turning North off frees East, once the all-red has run
+    manual.toggle(Direction::North, 100).unwrap();
+    manual.toggle(Direction::East, 100).unwrap();
+    assert!(manual.greens().is_empty());
+    manual.update(100 + CLEARANCE_FRAMES - 1);
+    assert_eq!(manual.waiting(), [Direction::East]);
+    manual.update(100 + CLEARANCE_FRAMES);
+    assert_eq!(manual.greens(), [Direction::East]);
+    assert!(manual.waiting().is_empty());
+
+    // A waiting green toggled again is cancelled
+    let mut manual = north_green(0);
+    manual.toggle(Direction::North, 100).unwrap();
+    manual.toggle(Direction::West, 100).unwrap();
+    manual.toggle(Direction::West, 110).unwrap();
+    manual.update(100 + CLEARANCE_FRAMES);
+    assert!(manual.greens().is_empty() && manual.waiting().is_empty());
+}
@@ -0,0 +1,100 @@
+use road_intersection::intersection::Controller;
+use road_intersection::network::Network;
+use road_intersection::simulation::Simulation;
+use road_intersection::vehicle::{Direction, TurnRatios};
+
+const STRAIGHT: TurnRatios = TurnRatios {
+    straight: 1.,
+    right: 0.,
+    left: 0.,
+};
+
+// Two intersections along an arterial, operated by hand, everyone going straight on
+fn corridor() -> Simulation {
+    let mut sim = Simulation::new(Network::corridor(2), 1);
+    sim.set_controller(|| Controller::External);
+    sim.turn_ratios = STRAIGHT;
+    sim
+}
+
+#[test]
+fn grid_nodes_lead_to_their_neighbours() {
+    // 0 1 2
+    // 3 4 5
+    let grid = Network::grid(3, 2);
+    assert_eq!(grid.next_node(0, Direction::East), Some(1));
+    assert_eq!(grid.next_node(0, Direction::South), Some(3));
+    assert_eq!(grid.next_node(4, Direction::North), Some(1));
+    assert_eq!(grid.next_node(4, Direction::West), Some(3));
+    assert_eq!(grid.next_node(0, Direction::North), None);
+    assert_eq!(grid.next_node(2, Direction::East), None);
+    assert_eq!(grid.next_node(5, Direction::South), None);
+    assert_eq!(grid.previous_node(4, Direction::East), Some(3));
+}
+
+#[test]
+fn entry_nodes_are_on_the_boundary_facing_the_traffic() {
+    let grid = Network::grid(3, 2);
+    assert_eq!(grid.entry_nodes(Direction::East), vec![0, 3]);
+    assert_eq!(grid.entry_nodes(Direction::West), vec![2, 5]);
+    assert_eq!(grid.entry_nodes(Direction::South), vec![0, 1, 2]);
+    assert_eq!(grid.entry_nodes(Direction::North), vec![3, 4, 5]);
+
+    let corridor = Network::corridor(3);
+    assert_eq!(corridor.next_node(1, Direction::West), Some(0));
+    assert_eq!(corridor.next_node(1, Direction::North), None);
+    assert_eq!(corridor.entry_nodes(Direction::East), vec![0]);
+    assert_eq!(corridor.entry_nodes(Direction::North), vec![0, 1, 2]);
+}
+
+#[test]
+fn vehicles_are_handed_over_to_the_next_node() {
+    let mut sim = corridor();
+    for intersection in &mut sim.intersections {
+        intersection.set_green(Some(Direction::East));
+    }
+    assert!(sim.spawn_at(Direction::East, 0));
+    let id = sim.vehicles[0].id;
+    let link = sim.network.outgoing_link(0, Direction::East);
+    for _ in 0..600 {
+        sim.step();
+        let v = sim.vehicles.iter().find(|v| v.id == id).expect("still in the world");
+        if v.node == 1 {
+            assert!(!v.in_intersection && !v.has_turned);
+            assert_eq!(v.direction, Direction::East);
+            assert_eq!(v.link, Some(link));
+            assert!(v.rect.x() < sim.network.nodes[1].center.0 - 50);
+            return;
+        }
+    }
+    panic!("vehicle never reached the next intersection");
+}
+
+#[test]
+fn vehicles_wait_outside_a_box_they_cannot_leave() {
+    // The downstream light stays red, so the link between the two fills up
+    let mut sim = corridor();
+    sim.intersections[0].set_green(Some(Direction::East));
+    sim.intersections[1].set_green(None);
+    for _ in 0..3600 {
+        sim.spawn_at(Direction::East, 0);
+        sim.step();
+    }
+    assert!(sim.vehicles.iter().any(|v| v.node == 0 && v.at_standstill()));
+    assert!(!sim.vehicles.iter().any(|v| v.node == 0 && v.in_intersection && v.at_standstill()));
+}
+
+#[test]
+fn runs_with_the_same_seed_number_vehicles_alike() {
+    let run = || {
+        let mut sim = Simulation::new(Network::grid(2, 2), 7);
+        sim.demand = 20.;
+        for _ in 0..600 {
+            sim.step();
+        }
+        sim.vehicles.iter().map(|v| (v.id, v.rect)).collect::<Vec<_>>()
+    };
+    let (first, second) = (run(), run());
+    assert!(!first.is_empty());
+    assert_eq!(first, second);
+}
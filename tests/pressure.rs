@@ -0,0 +1,94 @@
+use road_intersection::network::Network;
+use road_intersection::pressure::{approaching, queue_length, QueueControl, QueuePolicy};
+use road_intersection::vehicle::{Direction, Turn, Vehicle};
+
+const CENTER: (i32, i32) = (400, 400);
+
+// `count` vehicles queued nose to tail heading `direction` at the single intersection
+fn queue(direction: Direction, turn: Turn, count: i32) -> Vec<Vehicle> {
+    (0..count)
+        .map(|i| {
+            let edge = match direction {
+                Direction::North => CENTER.1 + 60 + i * 60,
+                Direction::South => CENTER.1 - 60 - i * 60,
+                Direction::East => CENTER.0 - 60 - i * 60,
+                Direction::West => CENTER.0 + 60 + i * 60,
+            };
+            let mut vehicle = Vehicle::new(i as u32 + 1, direction, 0, CENTER, edge, turn);
+            vehicle.stopped_frames = 30;
+            vehicle
+        })
+        .collect()
+}
+
+// `count` vehicles already on the link leaving heading `direction`
+fn downstream(network: &Network, direction: Direction, count: i32) -> Vec<Vehicle> {
+    let link = network.outgoing_link(0, direction);
+    (0..count)
+        .map(|i| {
+            let mut vehicle = Vehicle::new(100 + i as u32, direction, 0, CENTER, 0, Turn::Straight);
+            vehicle.has_turned = true;
+            vehicle.link = Some(link);
+            vehicle
+        })
+        .collect()
+}
+
+#[test]
+fn queues_count_only_stopped_vehicles() {
+    let mut vehicles = queue(Direction::North, Turn::Straight, 3);
+    vehicles[2].stopped_frames = 0;
+    vehicles.extend(queue(Direction::North, Turn::Left, 1));
+    assert_eq!(approaching(&vehicles, 0, Direction::North, None), 4);
+    assert_eq!(queue_length(&vehicles, 0, Direction::North, None), 3);
+    assert_eq!(queue_length(&vehicles, 0, Direction::North, Some(Turn::Left)), 1);
+    assert_eq!(queue_length(&vehicles, 0, Direction::South, None), 0);
+}
+
+#[test]
+fn longest_queue_serves_the_longest_approach() {
+    let network = Network::single();
+    let mut control = QueueControl::new(QueuePolicy::LongestQueue, &network, 0);
+    let mut vehicles = queue(Direction::North, Turn::Straight, 3);
+    vehicles.extend(queue(Direction::East, Turn::Straight, 1));
+    assert_eq!(control.update(0, &vehicles, 0), Some(Direction::North));
+
+    // A longer queue elsewhere only takes over after the minimum green
+    vehicles.extend(queue(Direction::West, Turn::Straight, 5));
+    assert_eq!(control.update(0, &vehicles, 60), Some(Direction::North));
+    assert_eq!(control.update(0, &vehicles, 600), Some(Direction::West));
+    // Nothing better: the green stays
+    assert_eq!(control.update(0, &vehicles, 1200), Some(Direction::West));
+}
+
+#[test]
+fn max_pressure_weighs_queues_against_the_links_they_feed() {
+    let network = Network::single();
+    // North has the longer queue, but the link it feeds is full
+    let mut vehicles = queue(Direction::North, Turn::Straight, 3);
+    vehicles.extend(downstream(&network, Direction::North, 5));
+    vehicles.extend(queue(Direction::East, Turn::Straight, 2));
+
+    let mut longest = QueueControl::new(QueuePolicy::LongestQueue, &network, 0);
+    assert_eq!(longest.update(0, &vehicles, 0), Some(Direction::North));
+    let mut pressure = QueueControl::new(QueuePolicy::MaxPressure, &network, 0);
+    assert_eq!(pressure.update(0, &vehicles, 0), Some(Direction::East));
+
+    // Turning right, the northbound queue feeds an empty link instead
+    let mut vehicles = queue(Direction::North, Turn::Right, 3);
+    vehicles.extend(downstream(&network, Direction::North, 5));
+    vehicles.extend(queue(Direction::East, Turn::Straight, 2));
+    let mut pressure = QueueControl::new(QueuePolicy::MaxPressure, &network, 0);
+    assert_eq!(pressure.update(0, &vehicles, 0), Some(Direction::North));
+}
+
+#[test]
+fn long_waits_are_served_first() {
+    let network = Network::single();
+    let mut control = QueueControl::new(QueuePolicy::MaxPressure, &network, 0);
+    let mut vehicles = queue(Direction::North, Turn::Straight, 5);
+    vehicles.extend(queue(Direction::South, Turn::Straight, 1));
+    assert_eq!(control.update(0, &vehicles, 0), Some(Direction::North));
+    vehicles.last_mut().unwrap().stopped_frames = 600;
+    assert_eq!(control.update(0, &vehicles, 600), Some(Direction::South));
+}
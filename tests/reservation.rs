@@ -0,0 +1,33 @@
+mod common;
+
+use road_intersection::safety::SafetyEvent;
+use road_intersection::simulation::Simulation;
+
+fn build(control: &str, demand: &str) -> Simulation {
+    common::build("grid", &[("control", control), ("demand", demand)], 5)
+}
+
+#[test]
+fn reservations_keep_vehicles_apart() {
+    let mut sim = build("reservation", "20");
+    assert!(sim.intersections.iter().all(|i| i.is_unsignalized()));
+    for _ in 0..12000 {
+        for event in sim.step() {
+            assert!(!matches!(event, SafetyEvent::Overlap { .. }), "{}", event);
+        }
+    }
+    assert!(sim.metrics.total.vehicles > 500);
+}
+
+#[test]
+fn reservations_beat_signals_on_identical_arrivals() {
+    let mut signals = build("signal", "10");
+    let mut reservations = build("reservation", "10");
+    for _ in 0..12000 {
+        signals.step();
+        reservations.step();
+    }
+    let (signals, reservations) = (signals.metrics.total, reservations.metrics.total);
+    assert!(reservations.average_delay() < signals.average_delay());
+    assert!(reservations.stops_per_vehicle() < signals.stops_per_vehicle());
+}
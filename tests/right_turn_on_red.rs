@@ -0,0 +1,47 @@
+use road_intersection::right_turn_on_red::may_turn;
+use road_intersection::vehicle::{Direction, Turn, Vehicle, FULL_STOP_FRAMES};
+
+const CENTER: (i32, i32) = (400, 400);
+
+// A northbound vehicle standing at the stop line for `stopped_frames`
+fn waiting(turn: Turn, stopped_frames: i32) -> Vehicle {
+    let mut vehicle = Vehicle::new(1, Direction::North, 0, CENTER, CENTER.1 + 50, turn);
+    vehicle.stopped_frames = stopped_frames;
+    vehicle
+}
+
+#[test]
+fn refuses_before_a_full_stop() {
+    let vehicle = waiting(Turn::Right, FULL_STOP_FRAMES - 1);
+    assert!(vehicle.is_at_stop_line());
+    assert!(!may_turn(&vehicle, std::slice::from_ref(&vehicle)));
+    let vehicle = waiting(Turn::Right, FULL_STOP_FRAMES);
+    assert!(may_turn(&vehicle, std::slice::from_ref(&vehicle)));
+}
+
+#[test]
+fn refuses_with_conflicting_traffic() {
+    let vehicle = waiting(Turn::Right, FULL_STOP_FRAMES);
+    // Eastbound traffic closing in on the lane the turn leads into
+    let approaching = Vehicle::new(2, Direction::East, 0, CENTER, CENTER.0 - 80, Turn::Straight);
+    assert!(!may_turn(&vehicle, &[vehicle.clone(), approaching]));
+    // Southbound traffic in the box turning left into it
+    let mut crossing = Vehicle::new(3, Direction::South, 0, CENTER, CENTER.1 - 40, Turn::Left);
+    crossing.in_intersection = true;
+    assert!(!may_turn(&vehicle, &[vehicle.clone(), crossing]));
+}
+
+#[test]
+fn allows_otherwise() {
+    let vehicle = waiting(Turn::Right, FULL_STOP_FRAMES);
+    // Far off, going the other way, or waiting elsewhere
+    let distant = Vehicle::new(4, Direction::East, 0, CENTER, 0, Turn::Straight);
+    let opposite = Vehicle::new(5, Direction::West, 0, CENTER, CENTER.0 + 80, Turn::Straight);
+    let other_node = Vehicle::new(6, Direction::East, 1, CENTER, CENTER.0 - 80, Turn::Straight);
+    assert!(may_turn(&vehicle, &[vehicle.clone(), distant, opposite, other_node]));
+    // Only right turners, and only before entering the box
+    assert!(!may_turn(&waiting(Turn::Straight, FULL_STOP_FRAMES), &[]));
+    let mut inside = waiting(Turn::Right, FULL_STOP_FRAMES);
+    inside.in_intersection = true;
+    assert!(!may_turn(&inside, &[]));
+}
@@ -0,0 +1,79 @@
+use road_intersection::intersection::Controller;
+use road_intersection::network::Network;
+use road_intersection::roundabout::{self, MIN_GAP};
+use road_intersection::simulation::Simulation;
+use road_intersection::vehicle::{turned_direction, Direction, Turn, TurnRatios, Vehicle};
+
+const HEADINGS: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+fn circulating(angle: f32) -> Vehicle {
+    let mut vehicle = Vehicle::new(1, Direction::East, 0, (400, 400), 0, Turn::Straight);
+    vehicle.ring_angle = Some(angle);
+    vehicle
+}
+
+#[test]
+fn entering_vehicles_yield_to_circulating_traffic() {
+    let entering = Vehicle::new(2, Direction::North, 0, (400, 400), 800, Turn::Straight);
+    let entry = roundabout::entry_angle(Direction::North);
+    // Coming round towards the entry, or just past it
+    assert!(roundabout::must_yield(&entering, &[entering.clone(), circulating(entry - 0.5)]));
+    assert!(roundabout::must_yield(&entering, &[entering.clone(), circulating(entry + 0.5)]));
+    // Half the ring away, or on another roundabout
+    assert!(!roundabout::must_yield(&entering, &[entering.clone(), circulating(entry + 3.)]));
+    let mut elsewhere = circulating(entry - 0.5);
+    elsewhere.node = 1;
+    assert!(!roundabout::must_yield(&entering, &[entering.clone(), elsewhere]));
+    assert!(!roundabout::must_yield(&entering, std::slice::from_ref(&entering)));
+}
+
+#[test]
+fn queued_entries_never_crowd_the_ring() {
+    let mut sim = Simulation::new(Network::single(), 2);
+    sim.set_controller(|| Controller::Roundabout);
+    for frame in 0..6000 {
+        if frame % 20 == 0 {
+            for heading in HEADINGS {
+                sim.spawn_at(heading, 0);
+            }
+        }
+        sim.step();
+        let angles: Vec<f32> = sim.vehicles.iter().filter_map(|v| v.ring_angle).collect();
+        for (i, &a) in angles.iter().enumerate() {
+            for &b in &angles[i + 1..] {
+                let gap = roundabout::arc(a, b).min(roundabout::arc(b, a));
+                assert!(gap >= MIN_GAP - 0.2, "frame {}: {:?}", frame, angles);
+            }
+        }
+    }
+    assert!(sim.metrics.total.vehicles > 100);
+}
+
+#[test]
+fn every_exit_is_reachable() {
+    for heading in HEADINGS {
+        for turn in [Turn::Straight, Turn::Right, Turn::Left] {
+            let mut sim = Simulation::new(Network::single(), 1);
+            sim.set_controller(|| Controller::Roundabout);
+            let share = |t| if t == turn { 1. } else { 0. };
+            sim.turn_ratios = TurnRatios {
+                straight: share(Turn::Straight),
+                right: share(Turn::Right),
+                left: share(Turn::Left),
+            };
+            assert!(sim.spawn_at(heading, 0));
+            let id = sim.vehicles[0].id;
+            let mut left_ring = None;
+            for _ in 0..1200 {
+                sim.step();
+                match sim.vehicles.iter().find(|v| v.id == id) {
+                    Some(v) if v.has_turned && v.ring_angle.is_none() => left_ring = Some(v.direction),
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            assert_eq!(left_ring, Some(turned_direction(heading, turn)), "{:?} {:?}", heading, turn);
+            assert_eq!(sim.metrics.total.vehicles, 1, "{:?} {:?} never left the world", heading, turn);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+
+use road_intersection::network::Network;
+use road_intersection::routing::{RouteChoice, Router};
+use road_intersection::vehicle::{turned_direction, Direction, Turn, VELOCITY};
+
+// The link a vehicle entering on `origin` ends up on after making `turns`
+fn follow(network: &Network, origin: usize, turns: &VecDeque<Turn>) -> usize {
+    let mut link = origin;
+    for &turn in turns {
+        let node = network.links[link].to.expect("route runs off the world early");
+        link = network.outgoing_link(node, turned_direction(network.links[link].direction, turn));
+    }
+    link
+}
+
+#[test]
+fn shortest_path_crosses_fewest_intersections() {
+    // 0 1 2
+    // 3 4 5
+    // 6 7 8
+    let grid = Network::grid(3, 3);
+    let router = Router::new(&grid, RouteChoice::ShortestPath, VELOCITY);
+    let origin = grid.entry_link(0, Direction::East).unwrap();
+
+    let across = grid.outgoing_link(2, Direction::East);
+    let turns = router.route(&grid, origin, across).unwrap();
+    assert_eq!(turns, VecDeque::from([Turn::Straight; 3]));
+
+    let corner = grid.outgoing_link(8, Direction::South);
+    let turns = router.route(&grid, origin, corner).unwrap();
+    assert_eq!(turns.len(), 5);
+    assert_eq!(follow(&grid, origin, &turns), corner);
+
+    // Leaving the way it came in takes a loop round the block
+    let back = grid.outgoing_link(0, Direction::West);
+    let turns = router.route(&grid, origin, back).unwrap();
+    assert_eq!(follow(&grid, origin, &turns), back);
+}
+
+#[test]
+fn travel_time_routes_around_slow_links() {
+    let grid = Network::grid(3, 3);
+    let mut router = Router::new(&grid, RouteChoice::TravelTime, VELOCITY);
+    let origin = grid.entry_link(0, Direction::East).unwrap();
+    let across = grid.outgoing_link(2, Direction::East);
+    let jammed = grid.outgoing_link(0, Direction::East);
+    for _ in 0..50 {
+        router.record(jammed, 10_000);
+    }
+    let turns = router.route(&grid, origin, across).unwrap();
+    assert_eq!(follow(&grid, origin, &turns), across);
+    assert_ne!(turns[0], Turn::Straight);
+}
+
+#[test]
+fn unreachable_destinations_have_no_route() {
+    let grid = Network::grid(3, 3);
+    let router = Router::new(&grid, RouteChoice::ShortestPath, VELOCITY);
+    let origin = grid.entry_link(0, Direction::East).unwrap();
+    // Boundary links only ever lead into the world
+    let entry = grid.entry_link(8, Direction::West).unwrap();
+    assert_eq!(router.route(&grid, origin, entry), None);
+    // Nothing continues past an exit
+    let exit = grid.outgoing_link(2, Direction::East);
+    let elsewhere = grid.outgoing_link(8, Direction::South);
+    assert_eq!(router.route(&grid, exit, elsewhere), None);
+}
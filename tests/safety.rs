@@ -0,0 +1,71 @@
+use road_intersection::safety::{SafetyChecker, SafetyEvent};
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::{Direction, Turn, Vehicle};
+
+const CENTER: (i32, i32) = (400, 400);
+const STALL_FRAMES: i32 = 600;
+
+fn vehicle(id: u32, direction: Direction, edge: i32) -> Vehicle {
+    Vehicle::new(id, direction, 0, CENTER, edge, Turn::Straight)
+}
+
+// Checks `vehicles` for `frames` frames, moving them with `step` in between,
+// and returns every event raised
+fn run(vehicles: &mut [Vehicle], light: LightState, frames: i32, step: impl Fn(&mut [Vehicle])) -> Vec<SafetyEvent> {
+    let mut checker = SafetyChecker::new(STALL_FRAMES);
+    let mut events = Vec::new();
+    for frame in 1..=frames {
+        events.extend(checker.check(vehicles, |_| light, frame));
+        step(vehicles);
+    }
+    events
+}
+
+#[test]
+fn red_light_entry_is_reported_once() {
+    let mut vehicles = [vehicle(1, Direction::North, 460)];
+    let events = run(&mut vehicles, LightState::Red, 20, |vehicles| {
+        let v = &mut vehicles[0];
+        v.rect.set_y(v.rect.y() - 5);
+        v.in_intersection = v.rect.y() <= 450;
+    });
+    assert_eq!(events.len(), 1, "{:?}", events);
+    assert!(matches!(events[0], SafetyEvent::RedLightEntry { direction: Direction::North, .. }));
+
+    // Through a green, nothing to report
+    let mut vehicles = [vehicle(1, Direction::North, 460)];
+    let events = run(&mut vehicles, LightState::Green, 20, |vehicles| {
+        let v = &mut vehicles[0];
+        v.rect.set_y(v.rect.y() - 5);
+        v.in_intersection = v.rect.y() <= 450;
+    });
+    assert!(events.is_empty(), "{:?}", events);
+}
+
+#[test]
+fn conflicting_movements_are_reported_once() {
+    // Northbound and eastbound meet in the box and cross over each other
+    let mut vehicles = [vehicle(1, Direction::North, 450), vehicle(2, Direction::East, 350)];
+    let events = run(&mut vehicles, LightState::Green, 30, |vehicles| {
+        vehicles[0].rect.set_y(vehicles[0].rect.y() - 5);
+        vehicles[1].rect.set_x(vehicles[1].rect.x() + 5);
+        for v in vehicles.iter_mut() {
+            v.in_intersection = true;
+        }
+    });
+    assert_eq!(events.len(), 1, "{:?}", events);
+    let (a, b) = (vehicles[0].id, vehicles[1].id);
+    assert!(matches!(events[0], SafetyEvent::Overlap { a: x, b: y, .. } if (x, y) == (a.min(b), a.max(b))));
+}
+
+#[test]
+fn near_miss_is_reported_once() {
+    // The follower keeps closing on a leader standing in the lane, touching
+    // it after 20 frames and staying on it
+    let mut vehicles = [vehicle(1, Direction::North, 600), vehicle(2, Direction::North, 660)];
+    let events = run(&mut vehicles, LightState::Green, 40, |vehicles| {
+        vehicles[1].rect.set_y(vehicles[1].rect.y() - 1);
+    });
+    assert_eq!(events.len(), 1, "{:?}", events);
+    assert!(matches!(events[0], SafetyEvent::Overlap { frame: 22, .. }), "{:?}", events);
+}
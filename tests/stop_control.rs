@@ -0,0 +1,96 @@
+use road_intersection::intersection::Controller;
+use road_intersection::network::Network;
+use road_intersection::simulation::Simulation;
+use road_intersection::stop_control::StopControl;
+use road_intersection::traffic_light::LightState;
+use road_intersection::vehicle::{Direction, Turn, TurnRatios, Vehicle, FULL_STOP_FRAMES};
+
+fn all_way_stop() -> Simulation {
+    let mut sim = Simulation::new(Network::single(), 1);
+    sim.set_controller(|| Controller::StopSign(StopControl::all_way()));
+    sim.turn_ratios = TurnRatios {
+        straight: 1.,
+        right: 0.,
+        left: 0.,
+    };
+    sim
+}
+
+// Spawns one vehicle per heading, `delay` frames apart, and checks they enter
+// the box in that order
+fn assert_served_in_order(headings: &[Direction], delay: i32) {
+    let mut sim = all_way_stop();
+    let mut ids = Vec::new();
+    let mut entered = Vec::new();
+    for frame in 0..1200 {
+        if frame % delay == 0 && ids.len() < headings.len() {
+            assert!(sim.spawn_at(headings[ids.len()], 0));
+            ids.push(sim.vehicles.last().unwrap().id);
+        }
+        sim.step();
+        for v in sim.vehicles.iter().filter(|v| v.in_intersection) {
+            if !entered.contains(&v.id) {
+                entered.push(v.id);
+            }
+        }
+    }
+    assert_eq!(entered.len(), headings.len());
+    assert_eq!(
+        entered.iter().map(|id| ids.iter().position(|i| i == id).unwrap()).collect::<Vec<_>>(),
+        (0..headings.len()).collect::<Vec<_>>(),
+        "{:?} entered in the wrong order",
+        headings
+    );
+}
+
+#[test]
+fn first_to_stop_goes_first() {
+    assert_served_in_order(&[Direction::North, Direction::East, Direction::South, Direction::West], 20);
+    assert_served_in_order(&[Direction::West, Direction::South, Direction::East, Direction::North], 20);
+}
+
+#[test]
+fn a_full_stop_is_required() {
+    let mut sim = all_way_stop();
+    assert!(sim.spawn_at(Direction::North, 0));
+    let mut standing = 0;
+    for _ in 0..600 {
+        sim.step();
+        let v = &sim.vehicles[0];
+        if v.in_intersection {
+            assert!(standing >= FULL_STOP_FRAMES, "entered after standing {} frames", standing);
+            return;
+        }
+        standing = v.stopped_frames;
+    }
+    panic!("vehicle never entered the box");
+}
+
+#[test]
+fn side_road_waits_for_major_traffic_reaching_the_box() {
+    let center = (400, 400);
+    let minor = Vehicle::new(1, Direction::North, 0, center, center.1 + 50, Turn::Straight);
+    let released_past = |major_front: Option<i32>, stopped_frames: i32| {
+        let mut vehicles = vec![minor.clone()];
+        if let Some(front) = major_front {
+            let mut major = Vehicle::new(2, Direction::East, 0, center, front, Turn::Straight);
+            major.stopped_frames = stopped_frames;
+            vehicles.push(major);
+        }
+        let mut control = StopControl::two_way();
+        control.update(0, center, &vehicles, 0);
+        control.update(0, center, &vehicles, FULL_STOP_FRAMES);
+        control.signal_for(&minor) == LightState::Green
+    };
+    let released = |major_front| released_past(major_front, 0);
+    assert!(released(None));
+    // Far off, then about to enter
+    assert!(released(Some(0)));
+    assert!(!released(Some(center.0 - 60)));
+    // Its front just over the edge of the box, on the one frame before it
+    // counts as inside
+    assert!(!released(Some(center.0 - 47)));
+    // Standing at the edge, held up by traffic beyond the box, it is not
+    // closing in; waiting on it would leave both roads stuck
+    assert!(released_past(Some(center.0 - 60), FULL_STOP_FRAMES));
+}
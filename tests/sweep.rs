@@ -0,0 +1,51 @@
+mod common;
+
+use std::collections::{BTreeSet, HashMap};
+
+use common::options;
+use road_intersection::sweep::{combinations, expand};
+
+#[test]
+fn ranges_expand_inclusively() {
+    assert_eq!(expand("1..3").unwrap(), ["1", "2", "3"]);
+    assert_eq!(expand("5..15:5").unwrap(), ["5", "10", "15"]);
+    assert_eq!(expand("5..14:5").unwrap(), ["5", "10"]);
+    assert_eq!(expand("0.1..0.3:0.1").unwrap(), ["0.1", "0.2", "0.3"]);
+    assert!(expand("3..1").unwrap().is_empty());
+}
+
+#[test]
+fn lists_mix_values_and_ranges() {
+    assert_eq!(expand("adaptive").unwrap(), ["adaptive"]);
+    assert_eq!(expand("adaptive;max-pressure").unwrap(), ["adaptive", "max-pressure"]);
+    assert_eq!(expand("5..15:5;30").unwrap(), ["5", "10", "15", "30"]);
+}
+
+#[test]
+fn bad_ranges_are_rejected() {
+    assert!(expand("a..3").is_err());
+    assert!(expand("1..b").is_err());
+    assert!(expand("1..3:x").is_err());
+    assert!(expand("1..3:0").is_err());
+    assert!(expand("1..3:-1").is_err());
+    assert!(combinations(&options(&[("plan", "fixed"), ("demand", "1..x")])).is_err());
+}
+
+#[test]
+fn combinations_are_the_product_of_the_options() {
+    let combos = combinations(&options(&[
+        ("demand", "5..15:5"),
+        ("plan", "adaptive;fixed"),
+        ("seed", "1"),
+    ]))
+    .unwrap();
+    assert_eq!(combos.len(), 3 * 2);
+    let distinct: BTreeSet<_> = combos.iter().collect();
+    assert_eq!(distinct.len(), combos.len());
+    for combo in &combos {
+        assert_eq!(combo.keys().collect::<Vec<_>>(), ["demand", "plan", "seed"]);
+        assert_eq!(combo["seed"], "1");
+    }
+
+    assert_eq!(combinations(&HashMap::new()).unwrap().len(), 1);
+}
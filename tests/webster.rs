@@ -0,0 +1,75 @@
+mod common;
+
+use road_intersection::discharge;
+use road_intersection::intersection::Controller;
+use road_intersection::simulation::{Simulation, FRAMES_PER_SECOND, SAFE_DISTANCE};
+use road_intersection::vehicle::Direction;
+use road_intersection::webster;
+
+fn build(plan: &str) -> Simulation {
+    common::build("single", &[("plan", plan), ("demand", "6")], 4)
+}
+
+#[test]
+fn cycle_grows_with_flow_ratios() {
+    let cycles: Vec<i32> = [0., 0.3, 0.6, 0.9].iter().map(|&y| webster::optimal_cycle(4, y)).collect();
+    assert!(cycles.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", cycles);
+    // (1.5 L + 5) / (1 - Y) seconds
+    let lost = 4. * discharge::START_UP_LOST / FRAMES_PER_SECOND as f32;
+    let expected = (1.5 * lost + 5.) / 0.4 * FRAMES_PER_SECOND as f32;
+    assert!((cycles[2] as f32 - expected).abs() <= 1.);
+    assert_eq!(webster::optimal_cycle(4, 1.2), webster::optimal_cycle(4, 1.));
+}
+
+#[test]
+fn greens_follow_flow_ratios() {
+    let saturation = discharge::saturation_flow(SAFE_DISTANCE);
+    let flows = [
+        (Direction::South, 0.05 * saturation),
+        (Direction::West, 0.05 * saturation),
+        (Direction::North, 0.1 * saturation),
+        (Direction::East, 0.4 * saturation),
+    ];
+    let plan = webster::plan(&flows, SAFE_DISTANCE);
+    assert_eq!(plan.cycle, plan.phases.iter().map(|&(_, green)| green).sum::<i32>());
+    let green = |direction| plan.phases.iter().find(|&&(d, _)| d == direction).unwrap().1;
+    assert!(green(Direction::East) > green(Direction::North));
+    assert!(green(Direction::North) > green(Direction::South));
+    assert_eq!(green(Direction::South), green(Direction::West));
+    assert!((plan.cycle - webster::optimal_cycle(4, 0.6)).abs() <= 4);
+}
+
+#[test]
+fn plans_are_retimed_to_the_flows_seen() {
+    let mut sim = build("webster");
+    let Controller::FixedTime(initial) = &sim.intersections[0].controller else {
+        panic!("webster runs a fixed-time plan");
+    };
+    let initial = initial.clone();
+    for _ in 0..6000 {
+        sim.step();
+    }
+    let Controller::FixedTime(plan) = &sim.intersections[0].controller else {
+        panic!("webster runs a fixed-time plan");
+    };
+    assert_ne!(plan.phases, initial.phases);
+    assert!(plan.offset > 0);
+    // Flows have barely moved since the current cycle started
+    let flows = sim.webster.as_ref().unwrap().flows(0, sim.frame_count);
+    assert!(flows.iter().all(|&(_, flow)| flow > 0.));
+    let expected = webster::plan(&flows, sim.safe_distance).cycle;
+    assert!((plan.cycle - expected).abs() * 10 < expected, "{} against {}", plan.cycle, expected);
+}
+
+#[test]
+fn webster_stops_vehicles_less_than_the_hand_tuned_plan() {
+    let mut fixed = build("fixed");
+    let mut webster = build("webster");
+    for _ in 0..18000 {
+        fixed.step();
+        webster.step();
+    }
+    let (fixed, webster) = (fixed.metrics.total, webster.metrics.total);
+    assert!(webster.vehicles > 0);
+    assert!(webster.stops_per_vehicle() < fixed.stops_per_vehicle());
+}